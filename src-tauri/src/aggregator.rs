@@ -1,52 +1,177 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 
+use crate::attribution::usage_attribution;
+use crate::budgets::{budget_status, earliest_period_start};
+use crate::cache::cache_analytics;
+use crate::compare::compare_periods;
+use crate::context::SnapshotContext;
+use crate::heatmap::build_heatmap;
 use crate::models::*;
-use crate::parser::{collect_entries, UsageEntry};
-
-/// Pricing per million tokens (USD)
-/// (model_prefix, input, output, cache_read, cache_create)
-const PRICING: &[(&str, f64, f64, f64, f64)] = &[
-    ("claude-opus-4", 5.00, 25.00, 0.50, 6.25),
-    ("claude-sonnet-4", 3.00, 15.00, 0.30, 3.75),
-    ("claude-haiku-4", 1.00, 5.00, 0.10, 1.25),
-    ("claude-haiku-3", 0.25, 1.25, 0.03, 0.30),
-];
-
-pub fn model_display_name(model: &str) -> String {
-    if model.contains("opus-4-6") {
-        "Opus 4.6".into()
-    } else if model.contains("opus-4-5") || model.contains("opus-4") {
-        "Opus 4.5".into()
-    } else if model.contains("sonnet-4-5") || model.contains("sonnet-4") {
-        "Sonnet 4.5".into()
-    } else if model.contains("haiku-4") {
-        "Haiku 4.5".into()
-    } else if model.contains("haiku-3") {
-        "Haiku 3.5".into()
-    } else {
-        model.to_string()
+use crate::parser::{LimitHit, UsageEntry, UserPrompt};
+use crate::plans::{effective_limits, limit_usage};
+use crate::privacy::Privacy;
+use crate::registry::ModelRegistry;
+use crate::sessions::{per_active_hour, session_stats};
+use crate::tools::tool_usage;
+
+fn calculate_cost(registry: &ModelRegistry, usage: &ModelUsage) -> f64 {
+    registry.resolve(&usage.model).pricing.cost(&TokenUsage {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_read_input_tokens: usage.cache_read_tokens,
+        cache_creation_input_tokens: usage.cache_creation_tokens,
+    })
+}
+
+/// All tokens counted toward rate limits, cache included
+fn total_tokens(usage: &ModelUsage) -> u64 {
+    usage.input_tokens + usage.output_tokens + usage.cache_read_tokens + usage.cache_creation_tokens
+}
+
+/// All tokens of `family` in `models`. Plan caps and the weekly family
+/// rollup both count through this, so the two always agree.
+fn family_tokens(models: &HashMap<String, ModelUsage>, family: &str) -> u64 {
+    models
+        .values()
+        .filter(|m| m.family.eq_ignore_ascii_case(family))
+        .map(total_tokens)
+        .sum()
+}
+
+fn build_plan_usage(
+    settings: &AppSettings,
+    window_models: &HashMap<String, ModelUsage>,
+    weekly_models: &HashMap<String, ModelUsage>,
+) -> PlanUsage {
+    let limits = effective_limits(settings);
+    let model_caps = limits
+        .model_caps
+        .iter()
+        .map(|cap| ModelCapUsage {
+            family: cap.family.clone(),
+            window: limit_usage(family_tokens(window_models, &cap.family), cap.window_tokens),
+            weekly: limit_usage(family_tokens(weekly_models, &cap.family), cap.weekly_tokens),
+        })
+        .collect();
+
+    PlanUsage {
+        plan: settings.plan,
+        window: limit_usage(
+            window_models.values().map(total_tokens).sum(),
+            limits.window_tokens,
+        ),
+        weekly: limit_usage(
+            weekly_models.values().map(total_tokens).sum(),
+            limits.weekly_tokens,
+        ),
+        model_caps,
     }
 }
 
-fn get_pricing(model: &str) -> (f64, f64, f64, f64) {
-    for (prefix, input, output, cache_read, cache_create) in PRICING {
-        if model.starts_with(prefix) {
-            return (*input, *output, *cache_read, *cache_create);
+/// Window usage at each rate-limit hit, and the limit that history implies.
+/// Repeated hits while still throttled (retries within one window) collapse
+/// into the first one, which is the point the limit was actually reached.
+fn build_limit_history(
+    entries: &[UsageEntry],
+    hits: &[LimitHit],
+    window_hours: f64,
+) -> LimitHistory {
+    let window = Duration::seconds((window_hours * 3600.0) as i64);
+    let mut infos = Vec::new();
+    let mut last_counted: Option<DateTime<Utc>> = None;
+
+    for hit in hits {
+        if last_counted.is_some_and(|t| hit.timestamp - t < window) {
+            continue;
         }
+        last_counted = Some(hit.timestamp);
+        let start = hit.timestamp - window;
+        let window_tokens = entries
+            .iter()
+            .filter(|e| e.timestamp >= start && e.timestamp <= hit.timestamp)
+            .map(|e| e.usage.total())
+            .sum();
+        infos.push(LimitHitInfo {
+            timestamp: hit.timestamp.to_rfc3339(),
+            session_id: hit.session_id.clone(),
+            window_tokens,
+            resets_at: hit.resets_at.map(|t| t.to_rfc3339()),
+        });
+    }
+
+    let mut samples: Vec<u64> = infos
+        .iter()
+        .map(|h| h.window_tokens)
+        .filter(|t| *t > 0)
+        .collect();
+    samples.sort_unstable();
+    let suggested_usage_limit_tokens = match samples.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(samples[n / 2]),
+        n => Some((samples[n / 2 - 1] + samples[n / 2]) / 2),
+    };
+
+    LimitHistory {
+        hits: infos,
+        suggested_usage_limit_tokens,
     }
-    // Default to Sonnet pricing
-    (3.00, 15.00, 0.30, 3.75)
 }
 
-fn calculate_cost(usage: &ModelUsage) -> f64 {
-    let (input_rate, output_rate, cache_read_rate, cache_create_rate) = get_pricing(&usage.model);
-    let mtok = 1_000_000.0;
-    (usage.input_tokens as f64 / mtok) * input_rate
-        + (usage.output_tokens as f64 / mtok) * output_rate
-        + (usage.cache_read_tokens as f64 / mtok) * cache_read_rate
-        + (usage.cache_creation_tokens as f64 / mtok) * cache_create_rate
+/// Roll weekly model usage up by family, measuring each against its cap.
+/// Capped families are always listed so a cap with no usage still shows 0%.
+fn weekly_family_usage(
+    models: &HashMap<String, ModelUsage>,
+    caps: &[ModelCap],
+) -> Vec<FamilyUsage> {
+    let mut families: HashMap<String, FamilyUsage> = HashMap::new();
+    let new_family = |family: &str| FamilyUsage {
+        family: family.to_string(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+        message_count: 0,
+        cap: limit_usage(0, None),
+    };
+
+    for m in models.values() {
+        let family = m.family.to_lowercase();
+        let f = families
+            .entry(family.clone())
+            .or_insert_with(|| new_family(&family));
+        f.input_tokens += m.input_tokens;
+        f.output_tokens += m.output_tokens;
+        f.cache_read_tokens += m.cache_read_tokens;
+        f.cache_creation_tokens += m.cache_creation_tokens;
+        f.message_count += m.message_count;
+    }
+    for cap in caps.iter().filter(|c| c.weekly_tokens.is_some()) {
+        let family = cap.family.to_lowercase();
+        families
+            .entry(family.clone())
+            .or_insert_with(|| new_family(&family));
+    }
+
+    let mut result: Vec<FamilyUsage> = families
+        .into_values()
+        .map(|mut f| {
+            let limit = caps
+                .iter()
+                .find(|c| c.family.eq_ignore_ascii_case(&f.family))
+                .and_then(|c| c.weekly_tokens);
+            f.cap = limit_usage(family_tokens(models, &f.family), limit);
+            f
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.cap
+            .used_tokens
+            .cmp(&a.cap.used_tokens)
+            .then_with(|| a.family.cmp(&b.family))
+    });
+    result
 }
 
 struct AggregateResult {
@@ -56,6 +181,7 @@ struct AggregateResult {
 }
 
 fn aggregate_entries(
+    registry: &ModelRegistry,
     entries: &[UsageEntry],
     start: DateTime<Utc>,
     end: DateTime<Utc>,
@@ -73,10 +199,14 @@ fn aggregate_entries(
             sessions.insert(entry.session_id.clone());
         }
 
-        let model_usage = models.entry(entry.model.clone()).or_insert_with(|| ModelUsage {
-            model: entry.model.clone(),
-            display_name: model_display_name(&entry.model),
-            ..Default::default()
+        let model_usage = models.entry(entry.model.clone()).or_insert_with(|| {
+            let info = registry.resolve(&entry.model);
+            ModelUsage {
+                model: entry.model.clone(),
+                display_name: info.display_name,
+                family: info.family,
+                ..Default::default()
+            }
         });
         model_usage.input_tokens += entry.usage.input_tokens;
         model_usage.output_tokens += entry.usage.output_tokens;
@@ -92,14 +222,73 @@ fn aggregate_entries(
     }
 }
 
-/// Build the complete usage snapshot
-pub fn build_snapshot(window_hours: f64) -> UsageSnapshot {
-    let entries = collect_entries(window_hours);
-    let now = Utc::now();
+/// Prompts typed in `[start, end]` and the average responses, tokens and
+/// cost each one led to
+fn prompt_stats(
+    registry: &ModelRegistry,
+    prompts: &[UserPrompt],
+    agg: &AggregateResult,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> PromptStats {
+    let prompt_count = prompts
+        .iter()
+        .filter(|p| p.timestamp >= start && p.timestamp <= end)
+        .count() as u64;
+    let per_prompt = |value: f64| (prompt_count > 0).then(|| value / prompt_count as f64);
+    let tokens: u64 = agg.models.values().map(total_tokens).sum();
+    let cost: f64 = agg
+        .models
+        .values()
+        .map(|m| calculate_cost(registry, m))
+        .sum();
+    PromptStats {
+        prompt_count,
+        response_count: agg.message_count,
+        responses_per_prompt: per_prompt(agg.message_count as f64),
+        tokens_per_prompt: per_prompt(tokens as f64),
+        cost_per_prompt: per_prompt(cost),
+    }
+}
+
+/// Build the complete usage snapshot as of `ctx.now`, counting days and
+/// weeks in `ctx.tz`
+pub fn build_snapshot<Tz: TimeZone>(
+    settings: &AppSettings,
+    ctx: &SnapshotContext<Tz>,
+) -> UsageSnapshot {
+    let window_hours = settings.window_hours;
+    let now = ctx.now;
+    let heatmap_start = settings
+        .heatmap_days
+        .map(|days| now - Duration::days(days as i64));
+    let privacy = Privacy::from_settings(settings);
+    let history_start = heatmap_start
+        .into_iter()
+        .chain(earliest_period_start(ctx, &settings.budgets))
+        .min();
+    let mut collected = match history_start {
+        Some(start) if start < now - Duration::days(14) => {
+            ctx.collect_entries_since(start, &privacy)
+        }
+        _ => ctx.collect_entries(window_hours, &privacy),
+    };
+    let registry = ctx.registry();
+    // Budget scopes match real project paths, so measure them before
+    // projects are renamed for display
+    let budgets = budget_status(
+        &registry,
+        &collected.entries,
+        &settings.budgets,
+        privacy.aliases(),
+        ctx,
+    );
+    privacy.display(&mut collected);
+    let entries = collected.entries;
 
     // --- Rolling window ---
     let window_start = now - Duration::seconds((window_hours * 3600.0) as i64);
-    let window_agg = aggregate_entries(&entries, window_start, now);
+    let window_agg = aggregate_entries(&registry, &entries, window_start, now);
 
     let window = WindowUsage {
         total_input_tokens: window_agg.models.values().map(|m| m.input_tokens).sum(),
@@ -112,25 +301,32 @@ pub fn build_snapshot(window_hours: f64) -> UsageSnapshot {
             .sum(),
         message_count: window_agg.message_count,
         session_count: window_agg.sessions.len() as u64,
+        prompts: prompt_stats(
+            &registry,
+            &collected.prompts,
+            &window_agg,
+            window_start,
+            now,
+        ),
         window_start: window_start.to_rfc3339(),
         window_end: now.to_rfc3339(),
     };
 
-    // --- Weekly usage (Monday to now) ---
-    let today = now.date_naive();
-    let days_since_monday = today.weekday().num_days_from_monday();
-    let week_start_date = today - chrono::Duration::days(days_since_monday as i64);
-    let week_start = week_start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    // --- Weekly usage (local Monday to now) ---
+    let week_start_date = ctx.week_start_date();
+    let days_since_monday = (ctx.today() - week_start_date).num_days();
+    let week_start = ctx.start_of_day(week_start_date);
 
-    let weekly_agg = aggregate_entries(&entries, week_start, now);
+    let weekly_agg = aggregate_entries(&registry, &entries, week_start, now);
+    let limits = effective_limits(settings);
 
     // Daily breakdown
     let mut daily_breakdown = Vec::new();
     for day_offset in 0..=days_since_monday {
-        let day = week_start_date + chrono::Duration::days(day_offset as i64);
-        let day_start = day.and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let day_end = day.and_hms_opt(23, 59, 59).unwrap().and_utc();
-        let day_agg = aggregate_entries(&entries, day_start, day_end);
+        let day = week_start_date + Duration::days(day_offset);
+        let day_start = ctx.start_of_day(day);
+        let day_end = ctx.start_of_day(day + Duration::days(1)) - Duration::nanoseconds(1);
+        let day_agg = aggregate_entries(&registry, &entries, day_start, day_end);
         daily_breakdown.push(DailyUsage {
             date: day.to_string(),
             input_tokens: day_agg.models.values().map(|m| m.input_tokens).sum(),
@@ -139,6 +335,24 @@ pub fn build_snapshot(window_hours: f64) -> UsageSnapshot {
         });
     }
 
+    let sessions = session_stats(
+        &registry,
+        &entries,
+        &collected.prompts,
+        week_start,
+        now,
+        Duration::minutes(settings.idle_threshold_mins as i64),
+    );
+    // The same stretch of last week, which differs from a flat 7 days
+    // earlier when a DST change falls in between
+    let previous_week_start = ctx.start_of_day(week_start_date - Duration::days(7));
+    let active_secs: u64 = sessions.iter().map(|s| s.active_secs).sum();
+    let weekly_cost_usd: f64 = weekly_agg
+        .models
+        .values()
+        .map(|m| calculate_cost(&registry, m))
+        .sum();
+
     let weekly = WeeklyUsage {
         total_input_tokens: weekly_agg.models.values().map(|m| m.input_tokens).sum(),
         total_output_tokens: weekly_agg.models.values().map(|m| m.output_tokens).sum(),
@@ -150,24 +364,55 @@ pub fn build_snapshot(window_hours: f64) -> UsageSnapshot {
             .sum(),
         message_count: weekly_agg.message_count,
         session_count: weekly_agg.sessions.len() as u64,
+        prompts: prompt_stats(&registry, &collected.prompts, &weekly_agg, week_start, now),
+        active_secs,
+        messages_per_active_hour: per_active_hour(weekly_agg.message_count as f64, active_secs),
+        cost_per_active_hour: per_active_hour(weekly_cost_usd, active_secs),
         daily_breakdown,
+        by_family: weekly_family_usage(&weekly_agg.models, &limits.model_caps),
+        vs_previous_week: compare_periods(
+            &registry,
+            &entries,
+            (week_start, now),
+            (
+                previous_week_start,
+                previous_week_start + (now - week_start),
+            ),
+        ),
     };
 
+    let plan_usage = build_plan_usage(settings, &window_agg.models, &weekly_agg.models);
+    let limit_history = build_limit_history(&entries, &collected.limit_hits, window_hours);
+    let cache_analytics = cache_analytics(&registry, &entries, week_start, now);
+    let tool_usage = tool_usage(&registry, &entries, week_start, now);
+    let attribution = usage_attribution(&registry, &entries, week_start, now, &privacy);
+    let heatmap =
+        heatmap_start.map(|start| build_heatmap(&registry, &entries, start, now, &ctx.tz));
+
     // --- Model breakdown (from window) ---
     let mut models: Vec<ModelUsage> = window_agg.models.into_values().collect();
-    models.sort_by(|a, b| b.output_tokens.cmp(&a.output_tokens));
+    models.sort_by(|a, b| {
+        b.output_tokens
+            .cmp(&a.output_tokens)
+            .then_with(|| a.model.cmp(&b.model))
+    });
 
     // --- Cost estimate ---
-    let window_cost: f64 = models.iter().map(|m| calculate_cost(m)).sum();
-    let weekly_model_costs: Vec<ModelCost> = weekly_agg
+    let window_cost: f64 = models.iter().map(|m| calculate_cost(&registry, m)).sum();
+    let mut weekly_model_costs: Vec<ModelCost> = weekly_agg
         .models
         .values()
         .map(|m| ModelCost {
             model: m.model.clone(),
             display_name: m.display_name.clone(),
-            cost_usd: calculate_cost(m),
+            cost_usd: calculate_cost(&registry, m),
         })
         .collect();
+    weekly_model_costs.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.model.cmp(&b.model))
+    });
     let weekly_cost: f64 = weekly_model_costs.iter().map(|c| c.cost_usd).sum();
 
     let cost_estimate = CostEstimate {
@@ -176,11 +421,29 @@ pub fn build_snapshot(window_hours: f64) -> UsageSnapshot {
         by_model: weekly_model_costs,
     };
 
+    let unrecognized_models: BTreeSet<String> = entries
+        .iter()
+        .map(|e| e.model.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|m| !registry.resolve(m).recognized)
+        .map(String::from)
+        .collect();
+
     UsageSnapshot {
         window,
         weekly,
         models,
         cost_estimate,
+        plan_usage,
+        limit_history,
+        cache_analytics,
+        tool_usage,
+        attribution,
+        sessions,
+        heatmap,
+        budgets,
+        unrecognized_models: unrecognized_models.into_iter().collect(),
         last_updated: now.to_rfc3339(),
     }
 }
@@ -191,48 +454,36 @@ mod tests {
 
     #[test]
     fn test_model_display_name() {
-        assert_eq!(
-            model_display_name("claude-opus-4-6"),
-            "Opus 4.6"
-        );
-        assert_eq!(
-            model_display_name("claude-opus-4-5-20251101"),
-            "Opus 4.5"
-        );
-        assert_eq!(
-            model_display_name("claude-sonnet-4-5-20250929"),
-            "Sonnet 4.5"
-        );
-        assert_eq!(
-            model_display_name("claude-haiku-4-5-20251001"),
-            "Haiku 4.5"
-        );
-        assert_eq!(
-            model_display_name("claude-haiku-3-5-sonnet"),
-            "Haiku 3.5"
-        );
-        assert_eq!(
-            model_display_name("unknown-model"),
-            "unknown-model"
-        );
+        let registry = ModelRegistry::builtin();
+        let name = |model: &str| registry.resolve(model).display_name;
+        assert_eq!(name("claude-opus-4-6"), "Opus 4.6");
+        assert_eq!(name("claude-opus-4-5-20251101"), "Opus 4.5");
+        assert_eq!(name("claude-opus-4-1-20250805"), "Opus 4.1");
+        assert_eq!(name("claude-opus-4-20250514"), "Opus 4");
+        assert_eq!(name("claude-sonnet-4-5-20250929"), "Sonnet 4.5");
+        assert_eq!(name("claude-sonnet-4-20250514"), "Sonnet 4");
+        assert_eq!(name("claude-haiku-4-5-20251001"), "Haiku 4.5");
+        assert_eq!(name("claude-haiku-3-5-sonnet"), "Haiku 3.5");
+        assert_eq!(name("unknown-model"), "unknown-model");
     }
 
     #[test]
     fn test_get_pricing() {
-        let (i, o, cr, cc) = get_pricing("claude-opus-4-5-20251101");
-        assert_eq!(i, 5.00);
-        assert_eq!(o, 25.00);
-        assert_eq!(cr, 0.50);
-        assert_eq!(cc, 6.25);
+        let registry = ModelRegistry::builtin();
+        let pricing = registry.resolve("claude-opus-4-5-20251101").pricing;
+        assert_eq!(pricing.input, 5.00);
+        assert_eq!(pricing.output, 25.00);
+        assert_eq!(pricing.cache_read, 0.50);
+        assert_eq!(pricing.cache_create, 6.25);
 
-        let (i, o, _, _) = get_pricing("claude-sonnet-4-5-20250929");
-        assert_eq!(i, 3.00);
-        assert_eq!(o, 15.00);
+        let pricing = registry.resolve("claude-sonnet-4-5-20250929").pricing;
+        assert_eq!(pricing.input, 3.00);
+        assert_eq!(pricing.output, 15.00);
 
         // Unknown model defaults to Sonnet pricing
-        let (i, o, _, _) = get_pricing("unknown-model");
-        assert_eq!(i, 3.00);
-        assert_eq!(o, 15.00);
+        let pricing = registry.resolve("unknown-model").pricing;
+        assert_eq!(pricing.input, 3.00);
+        assert_eq!(pricing.output, 15.00);
     }
 
     #[test]
@@ -240,17 +491,143 @@ mod tests {
         let usage = ModelUsage {
             model: "claude-sonnet-4-5-20250929".into(),
             display_name: "Sonnet 4.5".into(),
+            family: "sonnet".into(),
             input_tokens: 1_000_000,
             output_tokens: 1_000_000,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
             message_count: 10,
         };
-        let cost = calculate_cost(&usage);
+        let cost = calculate_cost(&ModelRegistry::builtin(), &usage);
         // 1M input * $3/MTok + 1M output * $15/MTok = $18.00
         assert!((cost - 18.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_build_plan_usage() {
+        let registry = ModelRegistry::builtin();
+        let usage = |model: &str, tokens: u64| ModelUsage {
+            model: model.into(),
+            family: registry.resolve(model).family,
+            output_tokens: tokens,
+            ..Default::default()
+        };
+        let window: HashMap<String, ModelUsage> = [
+            ("o".to_string(), usage("claude-opus-4-6", 300)),
+            ("s".to_string(), usage("claude-sonnet-4-5-20250929", 700)),
+        ]
+        .into();
+        let weekly = window.clone();
+        let settings = AppSettings {
+            custom_limits: PlanLimits {
+                window_tokens: Some(2_000),
+                weekly_tokens: None,
+                model_caps: vec![ModelCap {
+                    family: "opus".into(),
+                    window_tokens: None,
+                    weekly_tokens: Some(600),
+                }],
+            },
+            ..Default::default()
+        };
+
+        let plan = build_plan_usage(&settings, &window, &weekly);
+        assert_eq!(plan.window.used_tokens, 1_000);
+        assert_eq!(plan.window.utilization, Some(0.5));
+        assert_eq!(plan.weekly.utilization, None);
+        assert_eq!(plan.model_caps[0].weekly.used_tokens, 300);
+        assert_eq!(plan.model_caps[0].weekly.utilization, Some(0.5));
+    }
+
+    #[test]
+    fn test_weekly_family_usage() {
+        let registry = ModelRegistry::builtin();
+        let usage = |model: &str, tokens: u64| ModelUsage {
+            model: model.into(),
+            family: registry.resolve(model).family,
+            output_tokens: tokens,
+            message_count: 1,
+            ..Default::default()
+        };
+        let models: HashMap<String, ModelUsage> = [
+            ("a".to_string(), usage("claude-opus-4-6", 300)),
+            ("b".to_string(), usage("claude-opus-4-5-20251101", 320)),
+            ("c".to_string(), usage("claude-sonnet-4-5-20250929", 900)),
+            // A family spelled differently in a user's registry file
+            (
+                "d".to_string(),
+                ModelUsage {
+                    family: "Opus".into(),
+                    ..usage("claude-opus-5", 80)
+                },
+            ),
+        ]
+        .into();
+        let caps = vec![
+            ModelCap {
+                family: "Opus".into(),
+                window_tokens: None,
+                weekly_tokens: Some(1_000),
+            },
+            ModelCap {
+                family: "haiku".into(),
+                window_tokens: None,
+                weekly_tokens: Some(50),
+            },
+        ];
+
+        let families = weekly_family_usage(&models, &caps);
+        let names: Vec<&str> = families.iter().map(|f| f.family.as_str()).collect();
+        assert_eq!(names, vec!["sonnet", "opus", "haiku"]);
+        assert_eq!(families[1].message_count, 3);
+        assert_eq!(families[1].cap.utilization, Some(0.7));
+        assert_eq!(families[0].cap.limit_tokens, None);
+        assert_eq!(families[2].cap.used_tokens, 0);
+
+        // The plan's cap on the family counts the same usage
+        let settings = AppSettings {
+            custom_limits: PlanLimits {
+                model_caps: caps,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let plan = build_plan_usage(&settings, &models, &models);
+        let capped = &plan.model_caps[0].weekly;
+        assert_eq!(capped.used_tokens, families[1].cap.used_tokens);
+        assert_eq!(capped.utilization, families[1].cap.utilization);
+    }
+
+    #[test]
+    fn test_build_limit_history() {
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        let entry = |hours_after: i64, output_tokens: u64| UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: t0 + Duration::hours(hours_after),
+            session_id: "s1".into(),
+            ..Default::default()
+        };
+        let hit = |hours_after: i64| LimitHit {
+            timestamp: t0 + Duration::hours(hours_after),
+            session_id: "s1".into(),
+            project: String::new(),
+            resets_at: None,
+        };
+        let entries = vec![entry(0, 100), entry(1, 200), entry(20, 500), entry(21, 400)];
+        // The hit at 2h is a retry within the same window and is ignored
+        let hits = vec![hit(1), hit(2), hit(21)];
+
+        let history = build_limit_history(&entries, &hits, 5.0);
+        assert_eq!(history.hits.len(), 2);
+        assert_eq!(history.hits[0].window_tokens, 300);
+        assert_eq!(history.hits[1].window_tokens, 900);
+        assert_eq!(history.suggested_usage_limit_tokens, Some(600));
+    }
+
     #[test]
     fn test_aggregate_entries_filters_by_time() {
         let now = Utc::now();
@@ -265,6 +642,7 @@ mod tests {
                 },
                 timestamp: now - Duration::hours(1),
                 session_id: "s1".into(),
+                ..Default::default()
             },
             UsageEntry {
                 model: "claude-sonnet-4-5-20250929".into(),
@@ -276,10 +654,12 @@ mod tests {
                 },
                 timestamp: now - Duration::hours(10), // Outside 5-hour window
                 session_id: "s2".into(),
+                ..Default::default()
             },
         ];
 
-        let result = aggregate_entries(&entries, now - Duration::hours(5), now);
+        let registry = ModelRegistry::builtin();
+        let result = aggregate_entries(&registry, &entries, now - Duration::hours(5), now);
         assert_eq!(result.message_count, 1);
         let model = result.models.get("claude-sonnet-4-5-20250929").unwrap();
         assert_eq!(model.input_tokens, 100);
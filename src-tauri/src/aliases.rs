@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use glob::Pattern;
+
+use crate::models::ProjectAlias;
+
+/// Compiled `AppSettings::project_aliases`: which alias a project path goes
+/// by, and the tags of each alias
+#[derive(Debug, Clone, Default)]
+pub struct ProjectAliases {
+    rules: Vec<(Vec<Pattern>, String)>,
+    tags: BTreeMap<String, Vec<String>>,
+}
+
+impl ProjectAliases {
+    pub fn new(aliases: &[ProjectAlias]) -> Self {
+        let mut compiled = Self::default();
+        for alias in aliases {
+            let patterns = alias
+                .paths
+                .iter()
+                .filter_map(|p| Pattern::new(p).ok())
+                .collect();
+            compiled.rules.push((patterns, alias.alias.clone()));
+            let tags = compiled.tags.entry(alias.alias.clone()).or_default();
+            for tag in &alias.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        compiled
+    }
+
+    /// The alias of the first rule with a pattern matching `path`
+    pub fn resolve(&self, path: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(patterns, _)| patterns.iter().any(|p| p.matches(path)))
+            .map(|(_, alias)| alias.as_str())
+    }
+
+    /// Every alias with its tags
+    pub fn tagged(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.tags
+            .iter()
+            .map(|(alias, tags)| (alias.as_str(), tags.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_tags() {
+        let aliases = ProjectAliases::new(&[
+            ProjectAlias {
+                alias: "acme".into(),
+                paths: vec!["/work/acme-*".into(), "/work/worktrees/acme/**".into()],
+                tags: vec!["clients".into()],
+            },
+            ProjectAlias {
+                alias: "catch-all".into(),
+                paths: vec!["/work/**".into()],
+                tags: vec![],
+            },
+            ProjectAlias {
+                alias: "acme".into(),
+                paths: vec![],
+                tags: vec!["billable".into(), "clients".into()],
+            },
+        ]);
+
+        assert_eq!(aliases.resolve("/work/acme-api"), Some("acme"));
+        assert_eq!(
+            aliases.resolve("/work/worktrees/acme/fix-login"),
+            Some("acme")
+        );
+        // First matching rule wins
+        assert_eq!(aliases.resolve("/work/dotfiles"), Some("catch-all"));
+        assert_eq!(aliases.resolve("/home/me/notes"), None);
+
+        let tagged: Vec<(&str, &[String])> = aliases.tagged().collect();
+        assert_eq!(
+            tagged,
+            vec![
+                ("acme", &["clients".to_string(), "billable".to_string()][..]),
+                ("catch-all", &[][..])
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+/// Name suffixes of session transcripts, plain or compressed
+pub const TRANSCRIPT_SUFFIXES: &[&str] = &[".jsonl", ".jsonl.gz", ".jsonl.zst"];
+
+/// Name suffixes of tar archives of project directories
+pub const ARCHIVE_SUFFIXES: &[&str] = &[".tar", ".tar.gz", ".tgz", ".tar.zst"];
+
+fn has_suffix(path: &Path, suffixes: &[&str]) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    suffixes.iter().any(|s| name.ends_with(s))
+}
+
+pub fn is_transcript(path: &Path) -> bool {
+    has_suffix(path, TRANSCRIPT_SUFFIXES)
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    has_suffix(path, ARCHIVE_SUFFIXES)
+}
+
+/// Wrap `reader` in the decompressor its file name calls for
+fn decode<'a>(name: &Path, reader: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match name.extension().and_then(|e| e.to_str()) {
+        Some("gz") | Some("tgz") => Box::new(MultiGzDecoder::new(reader)),
+        Some("zst") => Box::new(zstd::Decoder::new(reader)?),
+        _ => Box::new(reader),
+    })
+}
+
+/// Open a transcript, decompressing `.gz` and `.zst` files on the fly
+pub fn open_transcript(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    Ok(Box::new(BufReader::new(decode(path, file)?)))
+}
+
+/// Call `f` with the path and contents of each transcript in a tar archive.
+/// The archive and its members may each be compressed; other members are
+/// skipped. Members already handed to `f` stay read if a later one fails.
+pub fn for_each_archived_transcript(
+    path: &Path,
+    mut f: impl FnMut(&Path, &mut dyn BufRead),
+) -> io::Result<()> {
+    let file = File::open(path)?;
+    let mut archive = tar::Archive::new(decode(path, file)?);
+    for member in archive.entries()? {
+        let member = member?;
+        let name = member.path()?.into_owned();
+        if !member.header().entry_type().is_file() || !is_transcript(&name) {
+            continue;
+        }
+        let mut reader = BufReader::new(decode(&name, member)?);
+        f(&name, &mut reader);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    const CONTENT: &[u8] = b"{\"type\":\"summary\"}\n";
+
+    fn read_all(mut reader: impl BufRead) -> Vec<u8> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_open_compressed_transcripts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let gz = dir.path().join("s1.jsonl.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gz).unwrap(), Default::default());
+        encoder.write_all(CONTENT).unwrap();
+        encoder.finish().unwrap();
+        let zst = dir.path().join("s2.jsonl.zst");
+        std::fs::write(&zst, zstd::encode_all(CONTENT, 0).unwrap()).unwrap();
+
+        for path in [&gz, &zst] {
+            assert!(is_transcript(path));
+            assert_eq!(read_all(open_transcript(path).unwrap()), CONTENT);
+        }
+        assert!(!is_transcript(Path::new("notes.txt.gz")));
+    }
+
+    #[test]
+    fn test_read_transcripts_in_tar_archive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("-Users-me-old.tar.zst");
+        let encoder = zstd::Encoder::new(File::create(&path).unwrap(), 0).unwrap();
+        let mut builder = tar::Builder::new(encoder.auto_finish());
+        for name in ["-Users-me-old/s1.jsonl", "-Users-me-old/notes.md"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(CONTENT.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, CONTENT).unwrap();
+        }
+        builder.into_inner().unwrap();
+
+        assert!(is_archive(&path));
+        let mut members: Vec<(PathBuf, Vec<u8>)> = vec![];
+        for_each_archived_transcript(&path, |name, reader| {
+            members.push((name.to_path_buf(), read_all(reader)));
+        })
+        .unwrap();
+        assert_eq!(
+            members,
+            vec![(PathBuf::from("-Users-me-old/s1.jsonl"), CONTENT.to_vec())]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    BranchUsage, MachineUsage, ProjectUsage, TagUsage, UsageAttribution, VersionUsage,
+};
+use crate::parser::UsageEntry;
+use crate::privacy::Privacy;
+use crate::registry::ModelRegistry;
+
+#[derive(Default)]
+struct Totals {
+    message_count: u64,
+    tokens: u64,
+    cost_usd: f64,
+}
+
+fn tokens_per_message(totals: &Totals) -> f64 {
+    if totals.message_count > 0 {
+        totals.tokens as f64 / totals.message_count as f64
+    } else {
+        0.0
+    }
+}
+
+/// Usage per project and git branch, per Claude Code version, per machine,
+/// per project and per tag for entries in `[start, end]`. Entry projects
+/// are already named by `Privacy::display`, so projects sharing an alias
+/// are summed together.
+/// Lines without a branch or version, this machine's own usage and projects
+/// without tags are grouped under an empty key. All lists are sorted by
+/// cost, highest first.
+pub fn usage_attribution(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    privacy: &Privacy,
+) -> UsageAttribution {
+    let mut by_branch: HashMap<(&str, &str), Totals> = HashMap::new();
+    let mut by_version: HashMap<&str, Totals> = HashMap::new();
+    let mut by_machine: HashMap<&str, Totals> = HashMap::new();
+    let mut by_project: HashMap<&str, Totals> = HashMap::new();
+    let mut by_tag: HashMap<&str, (Totals, BTreeSet<&str>)> = HashMap::new();
+
+    for entry in entries
+        .iter()
+        .filter(|e| e.timestamp >= start && e.timestamp <= end)
+    {
+        let tokens = entry.usage.total();
+        let cost = registry.resolve(&entry.model).pricing.cost(&entry.usage);
+        for totals in [
+            by_branch
+                .entry((&entry.project, &entry.git_branch))
+                .or_default(),
+            by_version.entry(&entry.version).or_default(),
+            by_machine.entry(&entry.machine).or_default(),
+            by_project.entry(&entry.project).or_default(),
+        ] {
+            totals.message_count += 1;
+            totals.tokens += tokens;
+            totals.cost_usd += cost;
+        }
+
+        let tags = privacy.tags(&entry.project);
+        let tags = if tags.is_empty() {
+            vec![""]
+        } else {
+            tags.iter().map(String::as_str).collect()
+        };
+        for tag in tags {
+            let (totals, projects) = by_tag.entry(tag).or_default();
+            totals.message_count += 1;
+            totals.tokens += tokens;
+            totals.cost_usd += cost;
+            projects.insert(&entry.project);
+        }
+    }
+
+    let mut by_branch: Vec<BranchUsage> = by_branch
+        .into_iter()
+        .map(|((project, branch), totals)| BranchUsage {
+            project: project.to_string(),
+            branch: branch.to_string(),
+            message_count: totals.message_count,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    by_branch.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| (&a.project, &a.branch).cmp(&(&b.project, &b.branch)))
+    });
+
+    let mut by_version: Vec<VersionUsage> = by_version
+        .into_iter()
+        .map(|(version, totals)| VersionUsage {
+            version: version.to_string(),
+            tokens_per_message: tokens_per_message(&totals),
+            message_count: totals.message_count,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    by_version.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.version.cmp(&b.version))
+    });
+
+    let mut by_machine: Vec<MachineUsage> = by_machine
+        .into_iter()
+        .map(|(machine, totals)| MachineUsage {
+            machine: machine.to_string(),
+            message_count: totals.message_count,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    by_machine.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.machine.cmp(&b.machine))
+    });
+
+    let mut by_project: Vec<ProjectUsage> = by_project
+        .into_iter()
+        .map(|(project, totals)| ProjectUsage {
+            project: project.to_string(),
+            tags: privacy.tags(project).to_vec(),
+            message_count: totals.message_count,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    by_project.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.project.cmp(&b.project))
+    });
+
+    let mut by_tag: Vec<TagUsage> = by_tag
+        .into_iter()
+        .map(|(tag, (totals, projects))| TagUsage {
+            tag: tag.to_string(),
+            projects: projects.into_iter().map(String::from).collect(),
+            message_count: totals.message_count,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    by_tag.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+
+    UsageAttribution {
+        by_branch,
+        by_version,
+        by_machine,
+        by_project,
+        by_tag,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AppSettings, ProjectAlias, TokenUsage};
+    use crate::parser::ParsedUsage;
+    use chrono::Duration;
+
+    fn entry(minutes: i64, branch: &str, version: &str, output_tokens: u64) -> UsageEntry {
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: t0 + Duration::minutes(minutes),
+            project: "/work/acme".into(),
+            git_branch: branch.into(),
+            version: version.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_usage_attribution() {
+        let mut entries = vec![
+            entry(0, "main", "2.0.13", 100_000),
+            entry(1, "feat/x", "2.0.13", 300_000),
+            entry(2, "feat/x", "2.0.14", 600_000),
+            entry(3, "feat/x", "2.0.14", 400_000),
+        ];
+        entries[3].machine = "workstation".into();
+        entries[0].project = "/home/me/notes".into();
+        let start = entries[0].timestamp;
+        let end = start + Duration::hours(1);
+        let privacy = Privacy::from_settings(&AppSettings {
+            project_aliases: vec![ProjectAlias {
+                alias: "acme".into(),
+                paths: vec!["/work/acme*".into()],
+                tags: vec!["clients".into(), "billable".into()],
+            }],
+            ..Default::default()
+        });
+        let mut parsed = ParsedUsage {
+            entries,
+            ..Default::default()
+        };
+        privacy.display(&mut parsed);
+
+        let attribution = usage_attribution(
+            &ModelRegistry::builtin(),
+            &parsed.entries,
+            start,
+            end,
+            &privacy,
+        );
+
+        let top = &attribution.by_branch[0];
+        assert_eq!(
+            (top.project.as_str(), top.branch.as_str()),
+            ("acme", "feat/x")
+        );
+        assert_eq!(top.message_count, 3);
+        // 1.3 MTok output at $15
+        assert!((top.cost_usd - 19.5).abs() < 1e-9);
+
+        let versions: Vec<(&str, f64)> = attribution
+            .by_version
+            .iter()
+            .map(|v| (v.version.as_str(), v.tokens_per_message))
+            .collect();
+        assert_eq!(versions, vec![("2.0.14", 500_000.0), ("2.0.13", 200_000.0)]);
+
+        let machines: Vec<(&str, u64)> = attribution
+            .by_machine
+            .iter()
+            .map(|m| (m.machine.as_str(), m.message_count))
+            .collect();
+        assert_eq!(machines, vec![("", 3), ("workstation", 1)]);
+
+        let projects: Vec<(&str, usize)> = attribution
+            .by_project
+            .iter()
+            .map(|p| (p.project.as_str(), p.tags.len()))
+            .collect();
+        assert_eq!(projects, vec![("acme", 2), ("/home/me/notes", 0)]);
+
+        // Usage counts toward each of a project's tags
+        let tags: Vec<(&str, u64)> = attribution
+            .by_tag
+            .iter()
+            .map(|t| (t.tag.as_str(), t.message_count))
+            .collect();
+        assert_eq!(tags, vec![("billable", 3), ("clients", 3), ("", 1)]);
+        assert_eq!(attribution.by_tag[2].projects, vec!["/home/me/notes"]);
+    }
+}
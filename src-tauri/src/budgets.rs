@@ -0,0 +1,219 @@
+use chrono::{DateTime, Datelike, Duration, Months, TimeZone, Utc};
+use glob::Pattern;
+
+use crate::aliases::ProjectAliases;
+use crate::context::SnapshotContext;
+use crate::models::{Budget, BudgetPeriod, BudgetStatus};
+use crate::parser::UsageEntry;
+use crate::registry::ModelRegistry;
+
+/// Start and end of the period around the context's current time
+fn period_bounds<Tz: TimeZone>(
+    ctx: &SnapshotContext<Tz>,
+    period: BudgetPeriod,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let (first, next) = match period {
+        BudgetPeriod::Daily => {
+            let today = ctx.today();
+            (today, today + Duration::days(1))
+        }
+        BudgetPeriod::Weekly => {
+            let monday = ctx.week_start_date();
+            (monday, monday + Duration::days(7))
+        }
+        BudgetPeriod::Monthly => {
+            let first = ctx.today().with_day(1).unwrap();
+            (first, first + Months::new(1))
+        }
+    };
+    (ctx.start_of_day(first), ctx.start_of_day(next))
+}
+
+/// Where the earliest current period of `budgets` starts, so that enough
+/// history is loaded to measure them
+pub fn earliest_period_start<Tz: TimeZone>(
+    ctx: &SnapshotContext<Tz>,
+    budgets: &[Budget],
+) -> Option<DateTime<Utc>> {
+    budgets.iter().map(|b| period_bounds(ctx, b.period).0).min()
+}
+
+/// Whether `entry` counts toward `budget`. Entries still carry their real
+/// project path, which the scope matches, as does the project's alias.
+fn in_scope(
+    budget: &Budget,
+    project: Option<&Pattern>,
+    aliases: &ProjectAliases,
+    registry: &ModelRegistry,
+    entry: &UsageEntry,
+) -> bool {
+    let project_matches = match (&budget.project, project) {
+        (None, _) => true,
+        (Some(_), Some(pattern)) => {
+            pattern.matches(&entry.project)
+                || aliases
+                    .resolve(&entry.project)
+                    .is_some_and(|alias| pattern.matches(alias))
+        }
+        // Settings validation rejects invalid patterns
+        (Some(_), None) => false,
+    };
+    let model_matches = budget.model.as_deref().is_none_or(|model| {
+        entry.model == model
+            || registry
+                .resolve(&entry.model)
+                .family
+                .eq_ignore_ascii_case(model)
+    });
+    project_matches && model_matches
+}
+
+/// Spend so far in the current period of each budget, and where it's
+/// heading. The projection extends the average rate since the period began
+/// to its end. `entries` must not have been through `Privacy::display` yet.
+pub fn budget_status<Tz: TimeZone>(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    budgets: &[Budget],
+    aliases: &ProjectAliases,
+    ctx: &SnapshotContext<Tz>,
+) -> Vec<BudgetStatus> {
+    budgets
+        .iter()
+        .map(|budget| {
+            let (start, end) = period_bounds(ctx, budget.period);
+            let project = budget.project.as_deref().and_then(|p| Pattern::new(p).ok());
+            let spent_usd: f64 = entries
+                .iter()
+                .filter(|e| e.timestamp >= start && e.timestamp <= ctx.now)
+                .filter(|e| in_scope(budget, project.as_ref(), aliases, registry, e))
+                .map(|e| registry.resolve(&e.model).pricing.cost(&e.usage))
+                .sum();
+
+            let elapsed = (ctx.now - start).num_seconds();
+            let length = (end - start).num_seconds();
+            let projected_usd = if elapsed > 0 {
+                spent_usd * length as f64 / elapsed as f64
+            } else {
+                spent_usd
+            };
+            BudgetStatus {
+                budget: budget.clone(),
+                period_start: start.to_rfc3339(),
+                period_end: end.to_rfc3339(),
+                spent_usd,
+                remaining_usd: (budget.amount_usd - spent_usd).max(0.0),
+                utilization: spent_usd / budget.amount_usd,
+                projected_usd,
+                projected_overrun_usd: (projected_usd > budget.amount_usd)
+                    .then_some(projected_usd - budget.amount_usd),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_tz::CentralEurope;
+    use crate::models::{ProjectAlias, TokenUsage};
+    use std::path::PathBuf;
+
+    fn entry(timestamp: &str, project: &str, model: &str, output_tokens: u64) -> UsageEntry {
+        UsageEntry {
+            model: model.into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: timestamp.parse().unwrap(),
+            project: project.into(),
+            ..Default::default()
+        }
+    }
+
+    fn budget(period: BudgetPeriod, amount_usd: f64) -> Budget {
+        Budget {
+            period,
+            amount_usd,
+            project: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_status() {
+        // Wednesday 2026-03-18, 13:00 in Central Europe
+        let ctx = SnapshotContext {
+            now: "2026-03-18T12:00:00Z".parse().unwrap(),
+            tz: CentralEurope,
+            projects_dir: PathBuf::new(),
+            registry_file: None,
+            imports_dir: None,
+        };
+        let sonnet = "claude-sonnet-4-5-20250929";
+        let opus = "claude-opus-4-1-20250805";
+        // Output at $15/MTok for Sonnet, $75/MTok for Opus
+        let entries = vec![
+            entry("2026-03-02T09:00:00Z", "/work/acme", opus, 100_000),
+            entry("2026-03-16T09:00:00Z", "/work/acme", sonnet, 200_000),
+            entry("2026-03-18T06:00:00Z", "/work/acme", sonnet, 300_000),
+            entry("2026-03-18T07:00:00Z", "/home/me/notes", sonnet, 100_000),
+        ];
+        let budgets = vec![
+            budget(BudgetPeriod::Daily, 10.0),
+            Budget {
+                project: Some("/work/*".into()),
+                ..budget(BudgetPeriod::Weekly, 25.0)
+            },
+            Budget {
+                model: Some("opus".into()),
+                ..budget(BudgetPeriod::Monthly, 100.0)
+            },
+            Budget {
+                project: Some("acme".into()),
+                ..budget(BudgetPeriod::Weekly, 25.0)
+            },
+        ];
+        let aliases = ProjectAliases::new(&[ProjectAlias {
+            alias: "acme".into(),
+            paths: vec!["/work/acme".into()],
+            tags: vec![],
+        }]);
+        assert_eq!(
+            earliest_period_start(&ctx, &budgets),
+            Some("2026-02-28T23:00:00Z".parse().unwrap())
+        );
+
+        let status = budget_status(
+            &ModelRegistry::builtin(),
+            &entries,
+            &budgets,
+            &aliases,
+            &ctx,
+        );
+
+        // $6 in the 13 hours since local midnight, heading for about $11
+        let daily = &status[0];
+        assert_eq!(daily.period_start, "2026-03-17T23:00:00+00:00");
+        assert!((daily.spent_usd - 6.0).abs() < 1e-9);
+        assert!((daily.remaining_usd - 4.0).abs() < 1e-9);
+        assert!((daily.projected_usd - 6.0 * 24.0 / 13.0).abs() < 1e-9);
+        assert!((daily.projected_overrun_usd.unwrap() - (6.0 * 24.0 / 13.0 - 10.0)).abs() < 1e-9);
+
+        // Only /work projects this week
+        let weekly = &status[1];
+        assert!((weekly.spent_usd - 7.5).abs() < 1e-9);
+        assert!(weekly.projected_overrun_usd.is_none());
+
+        // Only Opus this month; March has a DST change, so its length is
+        // measured between local midnights
+        let monthly = &status[2];
+        assert_eq!(monthly.period_end, "2026-03-31T22:00:00+00:00");
+        assert!((monthly.spent_usd - 7.5).abs() < 1e-9);
+        assert!((monthly.utilization - 0.075).abs() < 1e-9);
+
+        // Scopes also match aliases
+        assert!((status[3].spent_usd - 7.5).abs() < 1e-9);
+    }
+}
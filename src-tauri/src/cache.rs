@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{CacheAnalytics, CacheStats, TokenUsage};
+use crate::parser::UsageEntry;
+use crate::registry::ModelRegistry;
+
+#[derive(Default)]
+struct Totals {
+    input_tokens: u64,
+    cache_read_tokens: u64,
+    cache_creation_tokens: u64,
+    saved_usd: f64,
+    cache_write_usd: f64,
+    wasted_write_tokens: u64,
+    wasted_write_usd: f64,
+}
+
+impl Totals {
+    fn into_stats(self, key: String) -> CacheStats {
+        let total_input = self.input_tokens + self.cache_read_tokens + self.cache_creation_tokens;
+        CacheStats {
+            key,
+            input_tokens: self.input_tokens,
+            cache_read_tokens: self.cache_read_tokens,
+            cache_creation_tokens: self.cache_creation_tokens,
+            hit_ratio: if total_input > 0 {
+                self.cache_read_tokens as f64 / total_input as f64
+            } else {
+                0.0
+            },
+            saved_usd: self.saved_usd,
+            cache_write_usd: self.cache_write_usd,
+            wasted_write_tokens: self.wasted_write_tokens,
+            wasted_write_usd: self.wasted_write_usd,
+        }
+    }
+}
+
+/// Flag cache writes that nothing read back: a write is wasted when no later
+/// response in the same session and model had a cache read. This is a lower
+/// bound on waste. Usage records token counts, not which prefix a read hit,
+/// so one later read counts every earlier write in the session as used,
+/// including writes for prefixes that were abandoned. `entries` must be
+/// sorted by timestamp.
+fn wasted_writes(entries: &[&UsageEntry]) -> Vec<bool> {
+    let mut wasted = vec![false; entries.len()];
+    let mut read_later: HashMap<(&str, &str), bool> = HashMap::new();
+
+    for (i, entry) in entries.iter().enumerate().rev() {
+        let key = (entry.session_id.as_str(), entry.model.as_str());
+        let read_after = read_later.entry(key).or_insert(false);
+        if entry.usage.cache_creation_input_tokens > 0 && !*read_after {
+            wasted[i] = true;
+        }
+        if entry.usage.cache_read_input_tokens > 0 {
+            *read_after = true;
+        }
+    }
+    wasted
+}
+
+fn into_sorted(groups: HashMap<String, Totals>) -> Vec<CacheStats> {
+    let mut stats: Vec<CacheStats> = groups
+        .into_iter()
+        .map(|(key, totals)| totals.into_stats(key))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.saved_usd
+            .total_cmp(&a.saved_usd)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    stats
+}
+
+/// Cache hit ratio, savings and wasted writes per model, session and project
+/// for entries in `[start, end]`. Savings compare cache reads against paying
+/// the uncached input rate for the same tokens.
+pub fn cache_analytics(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> CacheAnalytics {
+    let in_range: Vec<&UsageEntry> = entries
+        .iter()
+        .filter(|e| e.timestamp >= start && e.timestamp <= end)
+        .collect();
+    let wasted = wasted_writes(&in_range);
+
+    let mut by_model: HashMap<String, Totals> = HashMap::new();
+    let mut by_session: HashMap<String, Totals> = HashMap::new();
+    let mut by_project: HashMap<String, Totals> = HashMap::new();
+
+    for (entry, is_wasted) in in_range.iter().zip(wasted) {
+        let pricing = registry.resolve(&entry.model).pricing;
+        let usage = &entry.usage;
+        let reads = usage.cache_read_input_tokens;
+        let saved = pricing.cost(&TokenUsage {
+            input_tokens: reads,
+            ..Default::default()
+        }) - pricing.cost(&TokenUsage {
+            cache_read_input_tokens: reads,
+            ..Default::default()
+        });
+        let write_cost = pricing.cost(&TokenUsage {
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            ..Default::default()
+        });
+
+        let mut groups = vec![
+            by_model.entry(entry.model.clone()).or_default(),
+            by_project.entry(entry.project.clone()).or_default(),
+        ];
+        if !entry.session_id.is_empty() {
+            groups.push(by_session.entry(entry.session_id.clone()).or_default());
+        }
+        for totals in groups {
+            totals.input_tokens += usage.input_tokens;
+            totals.cache_read_tokens += usage.cache_read_input_tokens;
+            totals.cache_creation_tokens += usage.cache_creation_input_tokens;
+            totals.saved_usd += saved;
+            totals.cache_write_usd += write_cost;
+            if is_wasted {
+                totals.wasted_write_tokens += usage.cache_creation_input_tokens;
+                totals.wasted_write_usd += write_cost;
+            }
+        }
+    }
+
+    CacheAnalytics {
+        by_model: into_sorted(by_model),
+        by_session: into_sorted(by_session),
+        by_project: into_sorted(by_project),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(minutes: i64, session: &str, read: u64, write: u64) -> UsageEntry {
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                input_tokens: 100,
+                cache_read_input_tokens: read,
+                cache_creation_input_tokens: write,
+                output_tokens: 10,
+            },
+            timestamp: t0 + Duration::minutes(minutes),
+            session_id: session.into(),
+            project: "/work/acme".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_analytics() {
+        let entries = vec![
+            entry(0, "s1", 0, 1_000_000),
+            entry(1, "s1", 1_000_000, 0),
+            // Written but the session ends without reading it back
+            entry(2, "s1", 0, 400_000),
+            entry(0, "s2", 0, 200_000),
+        ];
+        let start = entries[0].timestamp;
+        let end = start + Duration::hours(1);
+
+        let analytics = cache_analytics(&ModelRegistry::builtin(), &entries, start, end);
+
+        let s1 = analytics.by_session.iter().find(|s| s.key == "s1").unwrap();
+        assert_eq!(s1.wasted_write_tokens, 400_000);
+        // 0.4 MTok * $3.75
+        assert!((s1.wasted_write_usd - 1.5).abs() < 1e-9);
+        // 1 MTok read at $0.30 instead of $3.00
+        assert!((s1.saved_usd - 2.7).abs() < 1e-9);
+        // 1M read of 300 + 1M + 1.4M total input
+        assert!((s1.hit_ratio - 1_000_000.0 / 2_400_300.0).abs() < 1e-9);
+
+        let s2 = analytics.by_session.iter().find(|s| s.key == "s2").unwrap();
+        assert_eq!(s2.wasted_write_tokens, 200_000);
+
+        assert_eq!(analytics.by_project.len(), 1);
+        assert_eq!(analytics.by_project[0].wasted_write_tokens, 600_000);
+        assert_eq!(analytics.by_model[0].cache_creation_tokens, 1_600_000);
+    }
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::context::SnapshotContext;
+use crate::currency::Converted;
+use crate::imports::{export_usage, import_usage};
+use crate::models::AppSettings;
+use crate::privacy::Privacy;
+use crate::settings::{load_settings, read_settings, settings_path, SettingsError};
+use crate::team::team_snapshot;
+
+const EXPORT_USAGE: &str = "--export-usage <file> --machine <name> [--user <name>]";
+const IMPORT_USAGE: &str = "--import-usage <path> [--machine <name>]";
+const TEAM_REPORT_USAGE: &str = "--team-report <dir>";
+
+/// Run a one-off command instead of the app when `args` ask for one.
+/// Returns the process exit code, or None to start the app as usual.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let command = args.first().map(String::as_str)?;
+    let usage = match command {
+        "--parse-diagnostics" => return Some(print_parse_diagnostics()),
+        "--export-usage" => EXPORT_USAGE,
+        "--import-usage" => IMPORT_USAGE,
+        "--team-report" => TEAM_REPORT_USAGE,
+        _ => return None,
+    };
+    let Some(CommandArgs {
+        path: Some(path),
+        machine,
+        user,
+    }) = parse_command_args(&args[1..])
+    else {
+        return Some(usage_error(usage));
+    };
+    Some(match command {
+        "--export-usage" => run_export(path, machine, user),
+        "--import-usage" => run_import(path, machine),
+        _ => print_team_report(path),
+    })
+}
+
+/// The path and flags following a command, in any order
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CommandArgs<'a> {
+    path: Option<&'a str>,
+    machine: Option<&'a str>,
+    user: Option<&'a str>,
+}
+
+/// Split `args` into `--machine`/`--user` values and a single path. Returns
+/// None for unknown flags, a flag without a value, or a second path.
+fn parse_command_args(args: &[String]) -> Option<CommandArgs<'_>> {
+    let mut parsed = CommandArgs::default();
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        let (slot, value) = match arg {
+            "--machine" => (&mut parsed.machine, args.next()),
+            "--user" => (&mut parsed.user, args.next()),
+            _ if arg.starts_with("--") => return None,
+            _ => (&mut parsed.path, Some(arg)),
+        };
+        if slot.is_some() {
+            return None;
+        }
+        *slot = Some(value.filter(|v| !v.starts_with("--"))?);
+    }
+    Some(parsed)
+}
+
+fn usage_error(usage: &str) -> i32 {
+    eprintln!("Usage: claudepulse {}", usage);
+    2
+}
+
+fn print_json(value: &impl Serialize, what: &str) -> i32 {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to serialize {}: {}", what, e);
+            1
+        }
+    }
+}
+
+fn settings_error(message: &str) -> i32 {
+    eprintln!("Invalid settings: {}", message);
+    1
+}
+
+/// Print line counts and skip reasons for the transcripts the app reads
+fn print_parse_diagnostics() -> i32 {
+    // Read only: a diagnostic run must not migrate or rewrite the file
+    let settings = match read_settings(&settings_path()) {
+        Ok(settings) => settings,
+        Err(SettingsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            AppSettings::default()
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let ctx = SnapshotContext::system();
+    let privacy = match Privacy::checked(&settings) {
+        Ok(privacy) => privacy,
+        Err(e) => return settings_error(&e),
+    };
+    let mut collected = ctx.collect_entries(settings.window_hours, &privacy);
+    privacy.display(&mut collected);
+    print_json(&collected.diagnostics, "diagnostics")
+}
+
+/// Print this week's combined usage of everyone with exports in `dir`
+fn print_team_report(dir: &str) -> i32 {
+    let settings = load_settings();
+    let privacy = match Privacy::checked(&settings) {
+        Ok(privacy) => privacy,
+        Err(e) => return settings_error(&e),
+    };
+    let team = match team_snapshot(&SnapshotContext::system(), Path::new(dir), &privacy) {
+        Ok(team) => team,
+        Err(e) => {
+            eprintln!("Failed to read team directory: {}", e);
+            return 1;
+        }
+    };
+    print_json(&Converted::new(team, &settings), "team report")
+}
+
+/// Write this machine's usage to `file` for importing elsewhere or sharing
+/// with the team
+fn run_export(file: &str, machine: Option<&str>, user: Option<&str>) -> i32 {
+    let Some(machine) = machine else {
+        return usage_error(EXPORT_USAGE);
+    };
+    let ctx = SnapshotContext::system();
+    // Excluded projects never leave this machine, and with `anonymize` on
+    // neither do project paths or session IDs
+    let privacy = match Privacy::checked(&load_settings()) {
+        Ok(privacy) => privacy,
+        Err(e) => return settings_error(&e),
+    };
+    let mut export = export_usage(&ctx.projects_dir, machine, ctx.now, &privacy);
+    export.user = user.unwrap_or_default().to_string();
+    let result = serde_json::to_string(&export)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(file, json).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => {
+            println!("Exported {} responses to {}", export.entries.len(), file);
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to export usage: {}", e);
+            1
+        }
+    }
+}
+
+/// Import an export file or a copied `projects` directory
+fn run_import(path: &str, machine: Option<&str>) -> i32 {
+    let ctx = SnapshotContext::system();
+    let Some(imports_dir) = ctx.imports_dir.as_deref() else {
+        eprintln!("Failed to import usage: no imports directory");
+        return 1;
+    };
+    match import_usage(imports_dir, Path::new(path), machine, ctx.now) {
+        Ok(summary) => {
+            println!(
+                "Imported {} new responses from {} ({} already imported, {} total)",
+                summary.imported, summary.machine, summary.duplicates, summary.total
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to import usage: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_other_args_start_the_app() {
+        assert_eq!(run_cli(&[]), None);
+        // macOS passes a process serial number when launched from Finder
+        assert_eq!(run_cli(&["-psn_0_12345".to_string()]), None);
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_command_args_in_any_order() {
+        let expected = CommandArgs {
+            path: Some("laptop.json"),
+            machine: Some("laptop"),
+            user: None,
+        };
+        let path_first = args(&["laptop.json", "--machine", "laptop"]);
+        assert_eq!(parse_command_args(&path_first), Some(expected));
+        let flags_first = args(&["--machine", "laptop", "laptop.json"]);
+        assert_eq!(parse_command_args(&flags_first), Some(expected));
+        let no_path = args(&["--machine", "laptop"]);
+        assert_eq!(parse_command_args(&no_path).unwrap().path, None);
+    }
+
+    #[test]
+    fn test_bad_command_args_are_rejected() {
+        // A flag must not swallow the next flag as its value
+        assert_eq!(
+            parse_command_args(&args(&["--machine", "--user", "me", "dir"])),
+            None
+        );
+        assert_eq!(parse_command_args(&args(&["dir", "--machine"])), None);
+        assert_eq!(parse_command_args(&args(&["dir", "--verbose"])), None);
+        assert_eq!(parse_command_args(&args(&["one", "two"])), None);
+        assert_eq!(
+            parse_command_args(&args(&["--user", "a", "--user", "b", "dir"])),
+            None
+        );
+        assert_eq!(run_cli(&args(&["--import-usage"])), Some(2));
+        assert_eq!(
+            run_cli(&args(&["--import-usage", "--machine", "laptop"])),
+            Some(2)
+        );
+    }
+}
@@ -1,18 +1,124 @@
+use std::path::Path;
 use std::sync::Mutex;
 
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
 use crate::aggregator::build_snapshot;
-use crate::models::{AppSettings, UsageSnapshot};
+use crate::compare::compare_with_preceding;
+use crate::context::SnapshotContext;
+use crate::currency::Converted;
+use crate::heatmap::build_heatmap;
+use crate::models::{
+    AppSettings, ImportSummary, ParseDiagnostics, PeriodComparison, TeamSnapshot, UsageHeatmap,
+    UsageSnapshot,
+};
+use crate::privacy::Privacy;
+use crate::team::team_snapshot;
 
 pub struct AppState {
     pub settings: Mutex<AppSettings>,
 }
 
+fn current_settings(state: &State<'_, AppState>) -> Result<AppSettings, String> {
+    Ok(state.settings.lock().map_err(|e| e.to_string())?.clone())
+}
+
 #[tauri::command]
-pub fn get_usage_snapshot(state: State<'_, AppState>) -> Result<UsageSnapshot, String> {
+pub fn get_usage_snapshot(state: State<'_, AppState>) -> Result<Converted<UsageSnapshot>, String> {
     let settings = state.settings.lock().map_err(|e| e.to_string())?;
-    Ok(build_snapshot(settings.window_hours))
+    Privacy::checked(&settings)?;
+    let snapshot = build_snapshot(&settings, &SnapshotContext::system());
+    Ok(Converted::new(snapshot, &settings))
+}
+
+/// Usage by local weekday and hour over the last `days` days
+#[tauri::command]
+pub fn get_usage_heatmap(
+    state: State<'_, AppState>,
+    days: u32,
+) -> Result<Converted<UsageHeatmap>, String> {
+    if days == 0 || days > crate::settings::MAX_HEATMAP_DAYS {
+        return Err(format!(
+            "days must be between 1 and {}",
+            crate::settings::MAX_HEATMAP_DAYS
+        ));
+    }
+    let settings = current_settings(&state)?;
+    let ctx = SnapshotContext::system();
+    let start = ctx.now - chrono::Duration::days(days as i64);
+    let entries = ctx
+        .collect_entries_since(start, &Privacy::checked(&settings)?)
+        .entries;
+    let heatmap = build_heatmap(&ctx.registry(), &entries, start, ctx.now, &ctx.tz);
+    Ok(Converted::new(heatmap, &settings))
+}
+
+/// Compare usage between two RFC 3339 timestamps against the period of the
+/// same length immediately before it
+#[tauri::command]
+pub fn compare_periods(
+    state: State<'_, AppState>,
+    start: String,
+    end: String,
+) -> Result<Converted<PeriodComparison>, String> {
+    let parse = |s: &str| {
+        s.parse::<chrono::DateTime<chrono::Utc>>()
+            .map_err(|e| format!("invalid timestamp {:?}: {}", s, e))
+    };
+    let (start, end) = (parse(&start)?, parse(&end)?);
+    if end <= start {
+        return Err("end must be after start".into());
+    }
+    let settings = current_settings(&state)?;
+    let ctx = SnapshotContext::system();
+    let previous_start = start - (end - start);
+    let entries = ctx
+        .collect_entries_since(previous_start, &Privacy::checked(&settings)?)
+        .entries;
+    let comparison = compare_with_preceding(&ctx.registry(), &entries, start, end);
+    Ok(Converted::new(comparison, &settings))
+}
+
+/// Line counts and skip reasons for the transcripts behind the snapshot
+#[tauri::command]
+pub fn get_parse_diagnostics(state: State<'_, AppState>) -> Result<ParseDiagnostics, String> {
+    let settings = current_settings(&state)?;
+    let privacy = Privacy::checked(&settings)?;
+    let mut collected = SnapshotContext::system().collect_entries(settings.window_hours, &privacy);
+    privacy.display(&mut collected);
+    Ok(collected.diagnostics)
+}
+
+/// Import usage exported on another machine, or a copy of its `projects`
+/// directory, so the snapshot shows usage across machines
+#[tauri::command]
+pub fn import_usage(
+    app: AppHandle,
+    path: String,
+    machine: Option<String>,
+) -> Result<ImportSummary, String> {
+    let ctx = SnapshotContext::system();
+    let imports_dir = ctx.imports_dir.as_deref().ok_or("no imports directory")?;
+    let summary =
+        crate::imports::import_usage(imports_dir, Path::new(&path), machine.as_deref(), ctx.now)?;
+    let _ = app.emit("usage-data-changed", ());
+    Ok(summary)
+}
+
+/// Combined weekly usage of everyone sharing exports in the team directory
+#[tauri::command]
+pub fn get_team_snapshot(state: State<'_, AppState>) -> Result<Converted<TeamSnapshot>, String> {
+    let settings = current_settings(&state)?;
+    let team_dir = settings
+        .team_dir
+        .as_deref()
+        .ok_or("no team directory is configured")?;
+    let team = team_snapshot(
+        &SnapshotContext::system(),
+        Path::new(team_dir),
+        &Privacy::checked(&settings)?,
+    )?;
+    Ok(Converted::new(team, &settings))
 }
 
 #[tauri::command]
@@ -23,10 +129,14 @@ pub fn get_settings(state: State<'_, AppState>) -> Result<AppSettings, String> {
 
 #[tauri::command]
 pub fn update_settings(
+    app: AppHandle,
     state: State<'_, AppState>,
     new_settings: AppSettings,
 ) -> Result<(), String> {
     let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    *settings = new_settings.clone();
-    crate::settings::save_settings(&new_settings).map_err(|e| e.to_string())
+    crate::settings::save_settings(&new_settings).map_err(|e| e.to_string())?;
+    *settings = new_settings;
+    // Plan and window changes alter the snapshot
+    let _ = app.emit("usage-data-changed", ());
+    Ok(())
 }
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{MetricDelta, ModelComparison, PeriodComparison};
+use crate::parser::UsageEntry;
+use crate::registry::ModelRegistry;
+
+#[derive(Default)]
+struct Totals<'a> {
+    tokens: u64,
+    messages: u64,
+    sessions: HashSet<&'a str>,
+    cost_usd: f64,
+}
+
+impl<'a> Totals<'a> {
+    fn add(&mut self, registry: &ModelRegistry, entry: &'a UsageEntry) {
+        self.tokens += entry.usage.total();
+        self.messages += 1;
+        if !entry.session_id.is_empty() {
+            self.sessions.insert(&entry.session_id);
+        }
+        self.cost_usd += registry.resolve(&entry.model).pricing.cost(&entry.usage);
+    }
+}
+
+fn delta(current: f64, previous: f64) -> MetricDelta {
+    MetricDelta {
+        current,
+        previous,
+        change: current - previous,
+        percent_change: (previous != 0.0).then(|| (current - previous) / previous * 100.0),
+    }
+}
+
+fn deltas(current: &Totals, previous: &Totals) -> [MetricDelta; 4] {
+    [
+        delta(current.tokens as f64, previous.tokens as f64),
+        delta(current.messages as f64, previous.messages as f64),
+        delta(
+            current.sessions.len() as f64,
+            previous.sessions.len() as f64,
+        ),
+        delta(current.cost_usd, previous.cost_usd),
+    ]
+}
+
+/// Compare usage in `current` against `previous`, overall and per model.
+/// Models that appear in either period are included.
+pub fn compare_periods(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    current: (DateTime<Utc>, DateTime<Utc>),
+    previous: (DateTime<Utc>, DateTime<Utc>),
+) -> PeriodComparison {
+    let in_range = |e: &UsageEntry, (start, end): (DateTime<Utc>, DateTime<Utc>)| {
+        e.timestamp >= start && e.timestamp <= end
+    };
+
+    let mut totals = (Totals::default(), Totals::default());
+    let mut by_model: BTreeMap<&str, (Totals, Totals)> = BTreeMap::new();
+    for entry in entries {
+        let model = by_model.entry(&entry.model).or_default();
+        if in_range(entry, current) {
+            totals.0.add(registry, entry);
+            model.0.add(registry, entry);
+        } else if in_range(entry, previous) {
+            totals.1.add(registry, entry);
+            model.1.add(registry, entry);
+        }
+    }
+
+    let [tokens, messages, sessions, cost_usd] = deltas(&totals.0, &totals.1);
+    let mut models: Vec<ModelComparison> = by_model
+        .into_iter()
+        .filter(|(_, (cur, prev))| cur.messages > 0 || prev.messages > 0)
+        .map(|(model, (cur, prev))| {
+            let [tokens, messages, sessions, cost_usd] = deltas(&cur, &prev);
+            ModelComparison {
+                model: model.to_string(),
+                display_name: registry.resolve(model).display_name,
+                tokens,
+                messages,
+                sessions,
+                cost_usd,
+            }
+        })
+        .collect();
+    models.sort_by(|a, b| b.cost_usd.current.total_cmp(&a.cost_usd.current));
+
+    PeriodComparison {
+        current_start: current.0.to_rfc3339(),
+        current_end: current.1.to_rfc3339(),
+        previous_start: previous.0.to_rfc3339(),
+        previous_end: previous.1.to_rfc3339(),
+        tokens,
+        messages,
+        sessions,
+        cost_usd,
+        by_model: models,
+    }
+}
+
+/// Compare `[start, end]` against the period of the same length right before it
+pub fn compare_with_preceding(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> PeriodComparison {
+    let length = end - start;
+    compare_periods(registry, entries, (start, end), (start - length, start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenUsage;
+    use chrono::Duration;
+
+    fn entry(timestamp: &str, model: &str, session: &str, output_tokens: u64) -> UsageEntry {
+        UsageEntry {
+            model: model.into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: timestamp.parse().unwrap(),
+            session_id: session.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_periods() {
+        let sonnet = "claude-sonnet-4-5-20250929";
+        let opus = "claude-opus-4-6";
+        let entries = vec![
+            entry("2026-02-03T10:00:00Z", sonnet, "a", 1_000),
+            entry("2026-02-03T11:00:00Z", sonnet, "b", 1_000),
+            entry("2026-02-10T10:00:00Z", sonnet, "c", 3_000),
+            entry("2026-02-10T11:00:00Z", opus, "c", 1_000),
+        ];
+        let start: DateTime<Utc> = "2026-02-09T00:00:00Z".parse().unwrap();
+        let end = start + Duration::days(7);
+
+        let cmp = compare_with_preceding(&ModelRegistry::builtin(), &entries, start, end);
+        assert_eq!(cmp.previous_start, "2026-02-02T00:00:00+00:00");
+        assert_eq!(cmp.tokens.current, 4_000.0);
+        assert_eq!(cmp.tokens.previous, 2_000.0);
+        assert_eq!(cmp.tokens.percent_change, Some(100.0));
+        assert_eq!(cmp.sessions.change, -1.0);
+
+        let opus_cmp = cmp.by_model.iter().find(|m| m.model == opus).unwrap();
+        assert_eq!(opus_cmp.messages.previous, 0.0);
+        assert_eq!(opus_cmp.messages.percent_change, None);
+        let sonnet_cmp = cmp.by_model.iter().find(|m| m.model == sonnet).unwrap();
+        assert_eq!(sonnet_cmp.tokens.percent_change, Some(50.0));
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+
+use crate::imports::load_imports;
+use crate::parser::{collect_entries_since, merge_sorted, ParsedUsage};
+use crate::privacy::Privacy;
+use crate::registry::{ModelRegistry, REGISTRY_FILE_NAME};
+
+/// Everything a snapshot takes from its environment: the clock, the
+/// timezone days and weeks are counted in, and where transcripts and the
+/// model registry file live. Tests build one over fixture directories.
+#[derive(Clone)]
+pub struct SnapshotContext<Tz: TimeZone = Local> {
+    pub now: DateTime<Utc>,
+    pub tz: Tz,
+    /// Holds one directory of session transcripts per project
+    pub projects_dir: PathBuf,
+    /// User additions to the model registry; a missing file is ignored
+    pub registry_file: Option<PathBuf>,
+    /// Usage imported from other machines, one export file per machine
+    pub imports_dir: Option<PathBuf>,
+}
+
+impl SnapshotContext {
+    /// The system clock and timezone, reading from `~/.claude`
+    pub fn system() -> Self {
+        let claude_dir = dirs::home_dir().expect("No home dir").join(".claude");
+        Self {
+            now: Utc::now(),
+            tz: Local,
+            projects_dir: claude_dir.join("projects"),
+            registry_file: Some(claude_dir.join(REGISTRY_FILE_NAME)),
+            imports_dir: Some(claude_dir.join("claudepulse-imports")),
+        }
+    }
+}
+
+impl<Tz: TimeZone> SnapshotContext<Tz> {
+    pub fn registry(&self) -> ModelRegistry {
+        let mut registry = ModelRegistry::builtin();
+        if let Some(path) = &self.registry_file {
+            registry.merge_file(path);
+        }
+        registry
+    }
+
+    /// Local and imported usage recorded at or after `since` in projects
+    /// `privacy` allows. Responses imported from another machine that this
+    /// one also has count once.
+    pub fn collect_entries_since(&self, since: DateTime<Utc>, privacy: &Privacy) -> ParsedUsage {
+        let local = collect_entries_since(&self.projects_dir, since, privacy);
+        match &self.imports_dir {
+            Some(dir) => {
+                let mut sources = vec![local];
+                for mut imported in load_imports(dir, since) {
+                    privacy.filter(&mut imported);
+                    sources.push(imported);
+                }
+                merge_sorted(sources)
+            }
+            None => local,
+        }
+    }
+
+    /// Usage within `window_hours` of now
+    pub fn collect_entries(&self, window_hours: f64, privacy: &Privacy) -> ParsedUsage {
+        // The weekly view compares against last week, so we need up to 14 days
+        let window = Duration::seconds((window_hours * 3600.0) as i64);
+        self.collect_entries_since(
+            self.now - std::cmp::max(window, Duration::days(14)),
+            privacy,
+        )
+    }
+
+    /// The current date in the context's timezone
+    pub fn today(&self) -> NaiveDate {
+        self.now.with_timezone(&self.tz).date_naive()
+    }
+
+    /// Monday of the current week in the context's timezone
+    pub fn week_start_date(&self) -> NaiveDate {
+        let today = self.today();
+        today - Duration::days(today.weekday().num_days_from_monday() as i64)
+    }
+
+    /// The first instant of `date` in the context's timezone. Days that
+    /// start with a DST gap begin at the first hour that exists.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        (0..24)
+            .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+            .find_map(|local| self.tz.from_local_datetime(&local).earliest())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    }
+}
+
+#[cfg(test)]
+pub mod test_tz {
+    use chrono::{
+        Datelike, Duration, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, TimeZone,
+        Weekday,
+    };
+
+    /// Central European Time with EU daylight saving (+01:00, +02:00 from
+    /// 01:00 UTC on the last Sunday of March to 01:00 UTC on the last Sunday
+    /// of October), so tests don't depend on the machine's timezone
+    #[derive(Debug, Clone, Copy)]
+    pub struct CentralEurope;
+
+    fn last_sunday(year: i32, month: u32) -> NaiveDate {
+        let mut day = NaiveDate::from_ymd_opt(year, month + 1, 1).unwrap() - Duration::days(1);
+        while day.weekday() != Weekday::Sun {
+            day -= Duration::days(1);
+        }
+        day
+    }
+
+    fn is_summer(utc: &NaiveDateTime) -> bool {
+        let change = |month| last_sunday(utc.year(), month).and_hms_opt(1, 0, 0).unwrap();
+        *utc >= change(3) && *utc < change(10)
+    }
+
+    fn offset(summer: bool) -> FixedOffset {
+        FixedOffset::east_opt(if summer { 2 * 3600 } else { 3600 }).unwrap()
+    }
+
+    impl TimeZone for CentralEurope {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            CentralEurope
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> MappedLocalTime<FixedOffset> {
+            // A local time is valid under each offset that maps it back to
+            // itself: none in the spring gap, both in the autumn overlap
+            let valid: Vec<FixedOffset> = [offset(false), offset(true)]
+                .into_iter()
+                .filter(|o| {
+                    let utc = *local - Duration::seconds(o.local_minus_utc() as i64);
+                    self.offset_from_utc_datetime(&utc) == *o
+                })
+                .collect();
+            match valid[..] {
+                [] => MappedLocalTime::None,
+                [o] => MappedLocalTime::Single(o),
+                [a, b, ..] => MappedLocalTime::Ambiguous(b, a),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            offset(is_summer(utc))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_tz::CentralEurope;
+    use super::*;
+
+    fn context(now: &str) -> SnapshotContext<CentralEurope> {
+        SnapshotContext {
+            now: now.parse().unwrap(),
+            tz: CentralEurope,
+            projects_dir: PathBuf::new(),
+            registry_file: None,
+            imports_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_days_follow_the_context_timezone() {
+        // Sunday 23:30 UTC is already Monday in Berlin
+        let ctx = context("2026-02-08T23:30:00Z");
+        assert_eq!(ctx.today(), NaiveDate::from_ymd_opt(2026, 2, 9).unwrap());
+        assert_eq!(
+            ctx.start_of_day(ctx.today()).to_rfc3339(),
+            "2026-02-08T23:00:00+00:00"
+        );
+
+        // The day DST starts is 23 hours long
+        let spring = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        let length = ctx.start_of_day(spring.succ_opt().unwrap()) - ctx.start_of_day(spring);
+        assert_eq!(length.num_hours(), 23);
+    }
+}
@@ -0,0 +1,188 @@
+use serde::ser::Error as _;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::models::{AppSettings, DisplayCurrency};
+
+/// The rate for `code` in the user's table, whatever case it was entered in
+pub fn exchange_rate(settings: &AppSettings, code: &str) -> Option<f64> {
+    settings
+        .exchange_rates
+        .iter()
+        .find(|(c, _)| c.trim().eq_ignore_ascii_case(code))
+        .map(|(_, rate)| *rate)
+}
+
+/// The display currency from the settings, absent when costs are shown in
+/// USD only. Settings validation makes sure a set currency has a rate.
+pub fn display_currency(settings: &AppSettings) -> Option<DisplayCurrency> {
+    let code = settings.display_currency.as_deref()?.trim().to_uppercase();
+    if code == "USD" {
+        return None;
+    }
+    let rate = exchange_rate(settings, &code)?;
+    Some(DisplayCurrency {
+        code,
+        rate,
+        as_of: settings.exchange_rates_as_of.clone(),
+    })
+}
+
+/// Whether a field holds US dollars: `*_usd` fields and `cost_per_*` rates
+fn is_cost(key: &str) -> bool {
+    key.ends_with("_usd") || key.starts_with("cost_per_")
+}
+
+/// `cost_usd` becomes `cost_display`, `cost_per_prompt` becomes
+/// `cost_per_prompt_display`
+fn display_key(key: &str) -> String {
+    format!("{}_display", key.strip_suffix("_usd").unwrap_or(key))
+}
+
+/// A cost figure in the display currency. Period comparisons keep their
+/// percent change, which doesn't depend on the currency.
+fn scaled(value: &Value, rate: f64) -> Value {
+    match value {
+        Value::Number(n) => n.as_f64().map_or(Value::Null, |usd| (usd * rate).into()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, v)| match key.as_str() {
+                    "percent_change" => (key.clone(), v.clone()),
+                    _ => (key.clone(), scaled(v, rate)),
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Add a `*_display` field next to every cost field in `value`
+fn add_display_costs(value: &mut Value, rate: f64) {
+    match value {
+        Value::Object(fields) => {
+            let converted: Map<String, Value> = fields
+                .iter()
+                .filter(|(key, _)| is_cost(key))
+                .map(|(key, v)| (display_key(key), scaled(v, rate)))
+                .collect();
+            for v in fields.values_mut() {
+                add_display_costs(v, rate);
+            }
+            fields.extend(converted);
+        }
+        Value::Array(items) => {
+            for item in items {
+                add_display_costs(item, rate);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A command result whose costs are also given in the display currency.
+/// Without one it serializes exactly like the result itself; with one every
+/// USD figure keeps its field and gains a `*_display` sibling, and the
+/// currency is added as `display_currency`.
+pub struct Converted<T> {
+    pub value: T,
+    pub currency: Option<DisplayCurrency>,
+}
+
+impl<T> Converted<T> {
+    pub fn new(value: T, settings: &AppSettings) -> Self {
+        Self {
+            value,
+            currency: display_currency(settings),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Converted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(currency) = &self.currency else {
+            return self.value.serialize(serializer);
+        };
+        let mut value = serde_json::to_value(&self.value).map_err(S::Error::custom)?;
+        add_display_costs(&mut value, currency.rate);
+        if let Value::Object(fields) = &mut value {
+            let currency = serde_json::to_value(currency).map_err(S::Error::custom)?;
+            fields.insert("display_currency".into(), currency);
+        }
+        value.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CostEstimate, MetricDelta, ModelCost};
+    use serde_json::json;
+
+    #[test]
+    fn test_display_currency() {
+        let mut settings = AppSettings {
+            display_currency: Some("eur".into()),
+            exchange_rates: [("Eur".to_string(), 0.5)].into(),
+            exchange_rates_as_of: Some("2026-10-01".into()),
+            ..Default::default()
+        };
+        let currency = display_currency(&settings).unwrap();
+        assert_eq!((currency.code.as_str(), currency.rate), ("EUR", 0.5));
+
+        settings.display_currency = Some("USD".into());
+        assert!(display_currency(&settings).is_none());
+        settings.display_currency = None;
+        assert!(display_currency(&settings).is_none());
+    }
+
+    #[test]
+    fn test_converted_keeps_usd_alongside() {
+        let estimate = CostEstimate {
+            window_cost_usd: 2.0,
+            weekly_cost_usd: 10.0,
+            by_model: vec![ModelCost {
+                model: "claude-sonnet-4-5-20250929".into(),
+                display_name: "Sonnet 4.5".into(),
+                cost_usd: 10.0,
+            }],
+        };
+        let currency = DisplayCurrency {
+            code: "EUR".into(),
+            rate: 0.5,
+            as_of: None,
+        };
+
+        let plain = serde_json::to_value(Converted {
+            value: estimate.clone(),
+            currency: None,
+        })
+        .unwrap();
+        assert_eq!(plain, serde_json::to_value(&estimate).unwrap());
+
+        let converted = serde_json::to_value(Converted {
+            value: estimate,
+            currency: Some(currency),
+        })
+        .unwrap();
+        assert_eq!(converted["weekly_cost_usd"], json!(10.0));
+        assert_eq!(converted["weekly_cost_display"], json!(5.0));
+        assert_eq!(converted["window_cost_display"], json!(1.0));
+        assert_eq!(converted["by_model"][0]["cost_display"], json!(5.0));
+        assert_eq!(converted["display_currency"]["code"], json!("EUR"));
+
+        let delta = MetricDelta {
+            current: 4.0,
+            previous: 2.0,
+            change: 2.0,
+            percent_change: Some(100.0),
+        };
+        let mut value = json!({ "cost_usd": delta, "cost_per_prompt": null });
+        add_display_costs(&mut value, 0.5);
+        assert_eq!(
+            value["cost_display"],
+            json!({ "current": 2.0, "previous": 1.0, "change": 1.0, "percent_change": 100.0 })
+        );
+        assert_eq!(value["cost_per_prompt_display"], Value::Null);
+    }
+}
@@ -0,0 +1,132 @@
+//! Snapshot tests against fixture transcripts. Each case in
+//! `tests/fixtures/<case>` has a `projects` directory laid out like
+//! `~/.claude/projects` and the snapshot it should produce in
+//! `expected.json`. Run with `UPDATE_GOLDEN=1` to rewrite the expected files
+//! after an intended change, then review the diff.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::aggregator::build_snapshot;
+use crate::context::test_tz::CentralEurope;
+use crate::context::SnapshotContext;
+use crate::models::{AppSettings, Budget, BudgetPeriod, ProjectAlias, UsageSnapshot};
+
+/// Round floats so summation order can't change the output
+fn round_floats(value: &mut Value) {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            let rounded = (n.as_f64().unwrap() * 1e9).round() / 1e9;
+            *value = serde_json::json!(rounded);
+        }
+        Value::Array(items) => items.iter_mut().for_each(round_floats),
+        Value::Object(fields) => fields.values_mut().for_each(round_floats),
+        _ => {}
+    }
+}
+
+fn fixture_dir(case: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(case)
+}
+
+fn snapshot(case: &str, now: &str, settings: &AppSettings) -> UsageSnapshot {
+    let ctx = SnapshotContext {
+        now: now.parse().unwrap(),
+        tz: CentralEurope,
+        projects_dir: fixture_dir(case).join("projects"),
+        registry_file: None,
+        imports_dir: None,
+    };
+    build_snapshot(settings, &ctx)
+}
+
+fn check(case: &str, now: &str, settings: AppSettings) {
+    let dir = fixture_dir(case);
+    let mut actual = serde_json::to_value(snapshot(case, now, &settings)).unwrap();
+    round_floats(&mut actual);
+
+    let expected_path = dir.join("expected.json");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let json = serde_json::to_string_pretty(&actual).unwrap();
+        fs::write(&expected_path, json + "\n").unwrap();
+        return;
+    }
+    let expected: Value = fs::read_to_string(&expected_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_else(|| panic!("missing {}; run with UPDATE_GOLDEN=1", case));
+    assert!(
+        actual == expected,
+        "{} snapshot changed:\n{}",
+        case,
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+}
+
+/// 00:30 on Monday in Berlin is still Sunday in UTC. Sunday's work counts
+/// toward last week but stays in the rolling window.
+#[test]
+fn test_week_boundary() {
+    check(
+        "week_boundary",
+        "2026-02-08T23:30:00Z",
+        AppSettings::default(),
+    );
+}
+
+/// Clocks went forward on Sunday 2026-03-29, so last week began at 23:00 UTC
+/// rather than 22:00 and a reply at 23:30 the Sunday before stays out of it.
+/// The heatmap spans the 23-hour day.
+#[test]
+fn test_dst() {
+    let settings = AppSettings {
+        heatmap_days: Some(10),
+        ..Default::default()
+    };
+    check("dst", "2026-03-31T10:00:00Z", settings);
+}
+
+/// Streamed chunks repeat a response within a file and a resumed session
+/// copies the original's history; each response counts once.
+#[test]
+fn test_dedup() {
+    check("dedup", "2026-02-06T12:00:00Z", AppSettings::default());
+}
+
+/// Anonymizing renames projects only for display, so budgets scoped to a
+/// path or an alias still see their spend
+#[test]
+fn test_anonymize_keeps_budget_scopes() {
+    let weekly = |project: &str| Budget {
+        period: BudgetPeriod::Weekly,
+        amount_usd: 100.0,
+        project: Some(project.into()),
+        model: None,
+    };
+    let mut settings = AppSettings {
+        project_aliases: vec![ProjectAlias {
+            alias: "acme".into(),
+            paths: vec!["/Users/me/acme".into()],
+            tags: vec![],
+        }],
+        budgets: vec![weekly("/Users/me/*"), weekly("acme")],
+        ..Default::default()
+    };
+    let spent = |settings: &AppSettings| -> Vec<f64> {
+        snapshot("week_boundary", "2026-02-09T12:00:00Z", settings)
+            .budgets
+            .iter()
+            .map(|b| b.spent_usd)
+            .collect()
+    };
+
+    let plain = spent(&settings);
+    assert!(plain.iter().all(|&usd| usd > 0.0));
+    settings.anonymize = true;
+    assert_eq!(spent(&settings), plain);
+}
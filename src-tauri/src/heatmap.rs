@@ -0,0 +1,80 @@
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+
+use crate::models::{HeatmapCell, UsageHeatmap};
+use crate::parser::UsageEntry;
+use crate::registry::ModelRegistry;
+
+/// Bucket entries in `[start, end]` into a 7×24 grid of weekday (Monday
+/// first) by hour of day, in the given timezone.
+pub fn build_heatmap<Tz: TimeZone>(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: &Tz,
+) -> UsageHeatmap {
+    let mut cells = vec![vec![HeatmapCell::default(); 24]; 7];
+
+    for entry in entries {
+        if entry.timestamp < start || entry.timestamp > end {
+            continue;
+        }
+        let local = entry.timestamp.with_timezone(tz);
+        let cell =
+            &mut cells[local.weekday().num_days_from_monday() as usize][local.hour() as usize];
+        cell.tokens += entry.usage.total();
+        cell.messages += 1;
+        cell.cost_usd += registry.resolve(&entry.model).pricing.cost(&entry.usage);
+    }
+
+    UsageHeatmap {
+        range_start: start.to_rfc3339(),
+        range_end: end.to_rfc3339(),
+        cells,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenUsage;
+    use chrono::FixedOffset;
+
+    fn entry(timestamp: &str, output_tokens: u64) -> UsageEntry {
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: timestamp.parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_buckets_in_local_time() {
+        let entries = vec![
+            // Friday 23:30 UTC is Saturday 01:30 at UTC+2
+            entry("2026-02-06T23:30:00Z", 1_000_000),
+            entry("2026-02-06T23:45:00Z", 100),
+            // Outside the range
+            entry("2026-01-01T12:00:00Z", 5),
+        ];
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let start = "2026-02-01T00:00:00Z".parse().unwrap();
+        let end = "2026-02-08T00:00:00Z".parse().unwrap();
+
+        let heatmap = build_heatmap(&ModelRegistry::builtin(), &entries, start, end, &tz);
+        assert_eq!(heatmap.cells.len(), 7);
+        assert!(heatmap.cells.iter().all(|day| day.len() == 24));
+
+        let cell = &heatmap.cells[5][1];
+        assert_eq!(cell.messages, 2);
+        assert_eq!(cell.tokens, 1_000_100);
+        assert!((cell.cost_usd - 15.0015).abs() < 1e-9);
+
+        let total: u64 = heatmap.cells.iter().flatten().map(|c| c.messages).sum();
+        assert_eq!(total, 2);
+    }
+}
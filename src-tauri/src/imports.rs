@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ImportSummary, SkipReason};
+use crate::parser::{collect_entries_since, ParsedUsage, UsageEntry, UserPrompt};
+use crate::privacy::Privacy;
+use crate::settings::write_atomic;
+
+/// Current version of the usage export format
+pub const EXPORT_VERSION: u32 = 1;
+
+/// All usage recorded on one machine. Written by `--export-usage`, and kept
+/// in the same form per machine in the imports directory.
+#[derive(Serialize, Deserialize)]
+pub struct UsageExport {
+    pub version: u32,
+    pub machine: String,
+    /// Whose usage this is, for team reports; may be empty
+    #[serde(default)]
+    pub user: String,
+    pub exported_at: DateTime<Utc>,
+    pub entries: Vec<UsageEntry>,
+    #[serde(default)]
+    pub prompts: Vec<UserPrompt>,
+}
+
+fn all_time() -> DateTime<Utc> {
+    DateTime::from_timestamp(0, 0).unwrap()
+}
+
+/// Machine names become file names in the imports directory
+fn validate_machine(machine: &str) -> Result<(), String> {
+    let valid = !machine.is_empty()
+        && machine
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !machine.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid machine name {:?}: use letters, digits, '-', '_' and '.'",
+            machine
+        ))
+    }
+}
+
+/// Everything in the transcripts under `projects_dir` that `privacy` allows,
+/// tagged with `machine`. Paths stay real so the importing side's aliases
+/// and filters can match them, unless `privacy` anonymizes: then projects and
+/// sessions are hashed as in the app, and only usage leaves the machine.
+pub fn export_usage(
+    projects_dir: &Path,
+    machine: &str,
+    now: DateTime<Utc>,
+    privacy: &Privacy,
+) -> UsageExport {
+    let mut collected = collect_entries_since(projects_dir, all_time(), privacy);
+    if privacy.anonymizes() {
+        privacy.display(&mut collected);
+    }
+    UsageExport {
+        version: EXPORT_VERSION,
+        machine: machine.to_string(),
+        user: String::new(),
+        exported_at: now,
+        entries: collected.entries,
+        prompts: collected.prompts,
+    }
+}
+
+pub fn read_export(path: &Path) -> Result<UsageExport, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let export: UsageExport =
+        serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "{} was exported by a newer ClaudePulse (format version {})",
+            path.display(),
+            export.version
+        ));
+    }
+    Ok(export)
+}
+
+/// Identifies a response across imports. Entries without IDs fall back to
+/// their session and time, so importing the same file twice is harmless.
+fn entry_key(entry: &UsageEntry) -> (String, String) {
+    if entry.request_id.is_empty() && entry.message_id.is_empty() {
+        (entry.session_id.clone(), entry.timestamp.to_rfc3339())
+    } else {
+        (entry.request_id.clone(), entry.message_id.clone())
+    }
+}
+
+fn prompt_key(prompt: &UserPrompt) -> (String, String) {
+    if prompt.uuid.is_empty() {
+        (prompt.session_id.clone(), prompt.timestamp.to_rfc3339())
+    } else {
+        (prompt.uuid.clone(), String::new())
+    }
+}
+
+/// Import usage from an export file or a copied `projects` directory into
+/// `imports_dir`, merging with what was imported from the same machine
+/// before. `machine` is required for a directory and overrides the name
+/// recorded in an export file.
+pub fn import_usage(
+    imports_dir: &Path,
+    path: &Path,
+    machine: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<ImportSummary, String> {
+    let mut source = if path.is_dir() {
+        let machine = machine.ok_or("a machine name is required to import a projects directory")?;
+        // Filters apply when imported usage is loaded, not when it's stored
+        export_usage(path, machine, now, &Privacy::default())
+    } else {
+        read_export(path)?
+    };
+    if let Some(machine) = machine {
+        source.machine = machine.to_string();
+    }
+    validate_machine(&source.machine)?;
+
+    let store = imports_dir.join(format!("{}.json", source.machine));
+    let mut merged = if store.exists() {
+        read_export(&store)?
+    } else {
+        UsageExport {
+            version: EXPORT_VERSION,
+            machine: source.machine.clone(),
+            user: String::new(),
+            exported_at: now,
+            entries: vec![],
+            prompts: vec![],
+        }
+    };
+
+    let mut seen: HashSet<(String, String)> = merged.entries.iter().map(entry_key).collect();
+    let before = merged.entries.len();
+    let offered = source.entries.len();
+    merged.entries.extend(
+        source
+            .entries
+            .into_iter()
+            .filter(|e| seen.insert(entry_key(e))),
+    );
+    let imported = (merged.entries.len() - before) as u64;
+    merged.entries.sort_by_key(|e| e.timestamp);
+
+    let mut seen: HashSet<(String, String)> = merged.prompts.iter().map(prompt_key).collect();
+    merged.prompts.extend(
+        source
+            .prompts
+            .into_iter()
+            .filter(|p| seen.insert(prompt_key(p))),
+    );
+    merged.prompts.sort_by_key(|p| p.timestamp);
+    merged.version = EXPORT_VERSION;
+    merged.exported_at = now;
+
+    let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+    write_atomic(&store, &json).map_err(|e| format!("{}: {}", store.display(), e))?;
+
+    Ok(ImportSummary {
+        machine: merged.machine,
+        imported,
+        duplicates: offered as u64 - imported,
+        total: merged.entries.len() as u64,
+    })
+}
+
+/// Imported usage recorded at or after `since`, one result per machine.
+/// Import files that can't be read are reported in the diagnostics.
+pub fn load_imports(imports_dir: &Path, since: DateTime<Utc>) -> Vec<ParsedUsage> {
+    let mut unreadable = ParsedUsage::default();
+    let mut paths: Vec<PathBuf> = match fs::read_dir(imports_dir) {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect(),
+        Err(e) if e.kind() == ErrorKind::NotFound => vec![],
+        Err(_) => {
+            unreadable.skip(SkipReason::Io, imports_dir, 0);
+            vec![]
+        }
+    };
+    paths.sort();
+
+    let mut loaded: Vec<ParsedUsage> = paths
+        .iter()
+        .filter_map(|path| match read_export(path) {
+            Ok(export) => Some(export),
+            Err(_) => {
+                unreadable.skip(SkipReason::Io, path, 0);
+                None
+            }
+        })
+        .map(|export| ParsedUsage {
+            entries: export
+                .entries
+                .into_iter()
+                .filter(|e| e.timestamp >= since)
+                .map(|e| UsageEntry {
+                    machine: export.machine.clone(),
+                    ..e
+                })
+                .collect(),
+            prompts: export
+                .prompts
+                .into_iter()
+                .filter(|p| p.timestamp >= since)
+                .collect(),
+            ..Default::default()
+        })
+        .collect();
+    if !unreadable.diagnostics.skipped.is_empty() {
+        loaded.push(unreadable);
+    }
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::anonymize;
+    use std::io::Write;
+
+    fn write_session(projects_dir: &Path, requests: &[&str]) {
+        let project_dir = projects_dir.join("-Users-me-acme");
+        fs::create_dir_all(&project_dir).unwrap();
+        let mut file = fs::File::create(project_dir.join("s1.jsonl")).unwrap();
+        for request in requests {
+            writeln!(
+                file,
+                r#"{{"type":"assistant","requestId":"{}","timestamp":"2026-02-06T10:00:00Z","sessionId":"s1","message":{{"id":"msg_{}","model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":10}}}}}}"#,
+                request, request
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_anonymized_export_hides_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now: DateTime<Utc> = "2026-02-07T00:00:00Z".parse().unwrap();
+        write_session(dir.path(), &["req_1"]);
+        let privacy = Privacy::from_settings(&crate::models::AppSettings {
+            anonymize: true,
+            ..Default::default()
+        });
+
+        let export = export_usage(dir.path(), "laptop", now, &privacy);
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(!json.contains("Users-me-acme"));
+        assert!(!json.contains("\"s1\""));
+        assert_eq!(
+            export.entries[0].project,
+            anonymize("project", "-Users-me-acme")
+        );
+        assert_eq!(export.entries[0].session_id, anonymize("session", "s1"));
+        // Usage itself still dedupes on import
+        assert_eq!(export.entries[0].request_id, "req_1");
+    }
+
+    #[test]
+    fn test_import_merges_and_dedupes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now: DateTime<Utc> = "2026-02-07T00:00:00Z".parse().unwrap();
+        let imports_dir = dir.path().join("imports");
+
+        // An export file from the laptop
+        let laptop = dir.path().join("laptop");
+        write_session(&laptop, &["req_1", "req_2"]);
+        let export_file = dir.path().join("laptop.json");
+        let export = export_usage(&laptop, "laptop", now, &Privacy::default());
+        fs::write(&export_file, serde_json::to_string(&export).unwrap()).unwrap();
+
+        let summary = import_usage(&imports_dir, &export_file, None, now).unwrap();
+        assert_eq!(
+            (summary.imported, summary.duplicates, summary.total),
+            (2, 0, 2)
+        );
+
+        // A later copy of the same projects directory adds one response
+        write_session(&laptop, &["req_1", "req_2", "req_3"]);
+        let summary = import_usage(&imports_dir, &laptop, Some("laptop"), now).unwrap();
+        assert_eq!(summary.machine, "laptop");
+        assert_eq!(
+            (summary.imported, summary.duplicates, summary.total),
+            (1, 2, 3)
+        );
+
+        let imports = load_imports(&imports_dir, now - chrono::Duration::days(7));
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].entries.len(), 3);
+        assert!(imports[0].entries.iter().all(|e| e.machine == "laptop"));
+
+        // A damaged import is reported rather than skipped silently
+        fs::write(imports_dir.join("desk.json"), "{").unwrap();
+        let imports = load_imports(&imports_dir, now - chrono::Duration::days(7));
+        assert_eq!(imports.len(), 2);
+        let skipped = &imports[1].diagnostics.skipped[0];
+        assert_eq!(skipped.reason, SkipReason::Io);
+        assert!(skipped.samples[0].file.ends_with("desk.json"));
+
+        assert!(import_usage(&imports_dir, &laptop, None, now).is_err());
+        assert!(import_usage(&imports_dir, &laptop, Some("../etc"), now).is_err());
+    }
+}
@@ -1,10 +1,30 @@
 mod aggregator;
+mod aliases;
+mod archive;
+mod attribution;
+mod budgets;
+mod cache;
+mod cli;
 mod commands;
+mod compare;
+mod context;
+mod currency;
+#[cfg(test)]
+mod golden;
+mod heatmap;
+mod imports;
 mod models;
 mod parser;
+mod plans;
+mod privacy;
+mod registry;
+mod sessions;
 mod settings;
+mod team;
+mod tools;
 mod watcher;
 
+pub use cli::run_cli;
 use commands::AppState;
 use std::sync::Mutex;
 use tauri::{
@@ -70,11 +90,17 @@ pub fn run() {
 
             // Start file watcher for live updates
             watcher::start_watcher(app.handle().clone());
+            watcher::start_settings_watcher(app.handle().clone());
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_usage_snapshot,
+            commands::get_usage_heatmap,
+            commands::compare_periods,
+            commands::get_parse_diagnostics,
+            commands::import_usage,
+            commands::get_team_snapshot,
             commands::get_settings,
             commands::update_settings,
         ])
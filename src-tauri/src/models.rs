@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Token usage breakdown from a single API response
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -13,11 +14,22 @@ pub struct TokenUsage {
     pub output_tokens: u64,
 }
 
+impl TokenUsage {
+    /// All tokens counted toward rate limits, cache included
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_read_input_tokens
+            + self.cache_creation_input_tokens
+    }
+}
+
 /// Aggregated usage for a specific model
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct ModelUsage {
     pub model: String,
     pub display_name: String,
+    pub family: String,
     pub input_tokens: u64,
     pub output_tokens: u64,
     pub cache_read_tokens: u64,
@@ -25,6 +37,17 @@ pub struct ModelUsage {
     pub message_count: u64,
 }
 
+/// User prompts against the assistant responses they led to
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptStats {
+    pub prompt_count: u64,
+    /// Deduplicated API responses, the same as `message_count`
+    pub response_count: u64,
+    pub responses_per_prompt: Option<f64>,
+    pub tokens_per_prompt: Option<f64>,
+    pub cost_per_prompt: Option<f64>,
+}
+
 /// 5-hour rolling window usage
 #[derive(Debug, Clone, Serialize)]
 pub struct WindowUsage {
@@ -34,6 +57,7 @@ pub struct WindowUsage {
     pub total_cache_creation_tokens: u64,
     pub message_count: u64,
     pub session_count: u64,
+    pub prompts: PromptStats,
     pub window_start: String,
     pub window_end: String,
 }
@@ -47,6 +71,54 @@ pub struct DailyUsage {
     pub message_count: u64,
 }
 
+/// Weekly usage of one model family (opus, sonnet, haiku)
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilyUsage {
+    pub family: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub message_count: u64,
+    /// Total tokens against the family's weekly cap, if one is set
+    pub cap: LimitUsage,
+}
+
+/// A metric in two periods and how it changed
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricDelta {
+    pub current: f64,
+    pub previous: f64,
+    pub change: f64,
+    /// Absent when the previous value is zero
+    pub percent_change: Option<f64>,
+}
+
+/// Period-over-period change for one model
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelComparison {
+    pub model: String,
+    pub display_name: String,
+    pub tokens: MetricDelta,
+    pub messages: MetricDelta,
+    pub sessions: MetricDelta,
+    pub cost_usd: MetricDelta,
+}
+
+/// Usage in one period compared against an earlier one
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodComparison {
+    pub current_start: String,
+    pub current_end: String,
+    pub previous_start: String,
+    pub previous_end: String,
+    pub tokens: MetricDelta,
+    pub messages: MetricDelta,
+    pub sessions: MetricDelta,
+    pub cost_usd: MetricDelta,
+    pub by_model: Vec<ModelComparison>,
+}
+
 /// Weekly usage with daily breakdown
 #[derive(Debug, Clone, Serialize)]
 pub struct WeeklyUsage {
@@ -56,7 +128,15 @@ pub struct WeeklyUsage {
     pub total_cache_creation_tokens: u64,
     pub message_count: u64,
     pub session_count: u64,
+    pub prompts: PromptStats,
+    /// Summed over sessions, so overlapping sessions count twice
+    pub active_secs: u64,
+    pub messages_per_active_hour: Option<f64>,
+    pub cost_per_active_hour: Option<f64>,
     pub daily_breakdown: Vec<DailyUsage>,
+    pub by_family: Vec<FamilyUsage>,
+    /// This week so far against the same elapsed portion of last week
+    pub vs_previous_week: PeriodComparison,
 }
 
 /// Cost for a single model
@@ -75,6 +155,317 @@ pub struct CostEstimate {
     pub by_model: Vec<ModelCost>,
 }
 
+/// Token usage measured against an optional limit
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitUsage {
+    pub used_tokens: u64,
+    pub limit_tokens: Option<u64>,
+    /// `used_tokens / limit_tokens`, absent when there is no limit
+    pub utilization: Option<f64>,
+}
+
+/// Usage of a single model family against its plan cap
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCapUsage {
+    pub family: String,
+    pub window: LimitUsage,
+    pub weekly: LimitUsage,
+}
+
+/// Utilization against the selected plan's limits
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanUsage {
+    pub plan: Plan,
+    pub window: LimitUsage,
+    pub weekly: LimitUsage,
+    pub model_caps: Vec<ModelCapUsage>,
+}
+
+/// Prompt-cache effectiveness for one model, session or project
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub key: String,
+    pub input_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    /// Cache reads as a share of all input (uncached + cache read + cache write)
+    pub hit_ratio: f64,
+    /// What the cache reads would have cost at the uncached input rate, minus
+    /// what they did cost
+    pub saved_usd: f64,
+    pub cache_write_usd: f64,
+    /// Cache writes never read back later in the same session and model,
+    /// a lower bound: any later read counts every earlier write as used
+    pub wasted_write_tokens: u64,
+    pub wasted_write_usd: f64,
+}
+
+/// Weekly cache metrics at each level of grouping
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheAnalytics {
+    pub by_model: Vec<CacheStats>,
+    pub by_session: Vec<CacheStats>,
+    pub by_project: Vec<CacheStats>,
+}
+
+/// Usage on one git branch of one project
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchUsage {
+    pub project: String,
+    /// Empty for sessions outside a git repository
+    pub branch: String,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Usage recorded by one Claude Code version
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionUsage {
+    pub version: String,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    /// For spotting upgrades that changed token consumption
+    pub tokens_per_message: f64,
+}
+
+/// Usage recorded on one machine
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineUsage {
+    /// Name given when its usage was imported, empty for this machine
+    pub machine: String,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Usage in one project, or in all projects sharing an alias
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectUsage {
+    /// The alias, or the project path when it has none
+    pub project: String,
+    pub tags: Vec<String>,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Usage in all projects carrying one tag
+#[derive(Debug, Clone, Serialize)]
+pub struct TagUsage {
+    /// Empty for projects without tags
+    pub tag: String,
+    pub projects: Vec<String>,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Weekly usage by git branch, by Claude Code version, by machine, by
+/// project alias and by tag
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageAttribution {
+    pub by_branch: Vec<BranchUsage>,
+    pub by_version: Vec<VersionUsage>,
+    pub by_machine: Vec<MachineUsage>,
+    pub by_project: Vec<ProjectUsage>,
+    pub by_tag: Vec<TagUsage>,
+}
+
+/// Wall-clock and active time of one session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    pub project: String,
+    pub start: String,
+    pub end: String,
+    /// First to last response
+    pub duration_secs: u64,
+    /// Duration minus idle gaps longer than `AppSettings::idle_threshold_mins`
+    pub active_secs: u64,
+    pub prompt_count: u64,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub messages_per_active_hour: Option<f64>,
+    pub cost_per_active_hour: Option<f64>,
+}
+
+/// How often a tool was called and what the turns calling it cost. A turn
+/// that calls several tools counts toward each of them.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStats {
+    pub name: String,
+    pub invocations: u64,
+    /// Responses that called the tool at least once
+    pub turns: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Tool usage summed over all tools of one MCP server
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerStats {
+    pub server: String,
+    pub invocations: u64,
+    pub turns: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub tools: Vec<ToolStats>,
+}
+
+/// Weekly tool-call frequency and attributable usage
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolUsage {
+    pub tools: Vec<ToolStats>,
+    pub mcp_servers: Vec<McpServerStats>,
+}
+
+/// Usage within one weekday/hour bucket
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HeatmapCell {
+    pub tokens: u64,
+    pub messages: u64,
+    pub cost_usd: f64,
+}
+
+/// Usage by local day of week and hour of day
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageHeatmap {
+    pub range_start: String,
+    pub range_end: String,
+    /// `cells[weekday][hour]`, Monday = 0
+    pub cells: Vec<Vec<HeatmapCell>>,
+}
+
+/// A recorded rate-limit hit and the rolling-window usage leading up to it
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitHitInfo {
+    pub timestamp: String,
+    pub session_id: String,
+    pub window_tokens: u64,
+    pub resets_at: Option<String>,
+}
+
+/// Rate-limit hits seen in transcripts and the limit they imply
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitHistory {
+    pub hits: Vec<LimitHitInfo>,
+    /// Median window usage at which we were throttled, as a value for
+    /// `AppSettings::usage_limit_tokens`
+    pub suggested_usage_limit_tokens: Option<u64>,
+}
+
+/// Why a transcript line was left out of the usage numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The file or line couldn't be read
+    Io,
+    InvalidJson,
+    /// Valid JSON whose fields don't have the expected types
+    UnexpectedShape,
+    /// A line `type` the parser doesn't know about
+    UnknownType,
+    MissingMessage,
+    InvalidTimestamp,
+    MissingModel,
+    MissingUsage,
+    /// A `usage` object that doesn't have the expected shape
+    InvalidUsage,
+}
+
+/// Where a skipped line is; `line` is 1-based, or 0 when the whole file
+/// couldn't be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkipLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkipCount {
+    pub reason: SkipReason,
+    pub count: u64,
+    /// The first few locations, for finding format drift
+    pub samples: Vec<SkipLocation>,
+}
+
+/// How many transcript lines were used and why the others were skipped
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParseDiagnostics {
+    pub files_scanned: u64,
+    /// Non-empty lines
+    pub lines_read: u64,
+    pub entries_parsed: u64,
+    /// Further lines of an already counted API response
+    pub duplicate_lines: u64,
+    /// Responses dropped because another file already had them, as when a
+    /// resumed or forked session copies earlier lines
+    pub cross_file_duplicates: u64,
+    pub skipped: Vec<SkipCount>,
+}
+
+/// Outcome of importing another machine's usage
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub machine: String,
+    /// Responses not already imported from this machine
+    pub imported: u64,
+    /// Responses skipped because an earlier import had them
+    pub duplicates: u64,
+    /// Responses now kept for this machine
+    pub total: u64,
+}
+
+/// One team member's usage across all of their machines
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberUsage {
+    pub user: String,
+    /// Machines the member exported from
+    pub machines: Vec<String>,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// The whole team's usage of one model
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamModelUsage {
+    pub model: String,
+    pub display_name: String,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// The whole team's usage in one project
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamProjectUsage {
+    pub project: String,
+    /// Members who worked in the project
+    pub users: Vec<String>,
+    pub message_count: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Combined weekly usage of everyone who shares exports in a team directory
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamSnapshot {
+    pub period_start: String,
+    pub period_end: String,
+    pub total_tokens: u64,
+    pub total_cost_usd: f64,
+    pub by_user: Vec<MemberUsage>,
+    pub by_model: Vec<TeamModelUsage>,
+    pub by_project: Vec<TeamProjectUsage>,
+    /// Export files that couldn't be read
+    pub unreadable_files: Vec<String>,
+    pub last_updated: String,
+}
+
 /// Complete usage snapshot returned to frontend
 #[derive(Debug, Clone, Serialize)]
 pub struct UsageSnapshot {
@@ -82,25 +473,182 @@ pub struct UsageSnapshot {
     pub weekly: WeeklyUsage,
     pub models: Vec<ModelUsage>,
     pub cost_estimate: CostEstimate,
+    pub plan_usage: PlanUsage,
+    pub limit_history: LimitHistory,
+    pub cache_analytics: CacheAnalytics,
+    pub tool_usage: ToolUsage,
+    pub attribution: UsageAttribution,
+    /// This week's sessions, most recent first
+    pub sessions: Vec<SessionStats>,
+    /// Present when `AppSettings::heatmap_days` is set
+    pub heatmap: Option<UsageHeatmap>,
+    /// Current period of each of `AppSettings::budgets`, in the same order
+    pub budgets: Vec<BudgetStatus>,
+    /// Model IDs seen in the data that have no model registry entry; their
+    /// names and pricing are best guesses
+    pub unrecognized_models: Vec<String>,
     pub last_updated: String,
 }
 
-/// User-configurable settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Subscription plan the limits are taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Plan {
+    Pro,
+    Max5x,
+    Max20x,
+    /// Pay-as-you-go API billing, no token limits
+    Api,
+    /// Limits come from `AppSettings::custom_limits`
+    #[default]
+    Custom,
+}
+
+/// Cap for a single model family (matched against the model ID, e.g. "opus")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCap {
+    pub family: String,
+    #[serde(default)]
+    pub window_tokens: Option<u64>,
+    #[serde(default)]
+    pub weekly_tokens: Option<u64>,
+}
+
+/// Token limits defined by a plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanLimits {
+    pub window_tokens: Option<u64>,
+    pub weekly_tokens: Option<u64>,
+    pub model_caps: Vec<ModelCap>,
+}
+
+/// The currency costs are also given in, added to command results when
+/// `AppSettings::display_currency` is set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DisplayCurrency {
+    /// ISO 4217 code such as "EUR"
+    pub code: String,
+    /// Units of the currency per US dollar
+    pub rate: f64,
+    /// When the rate was last updated, if recorded
+    pub as_of: Option<String>,
+}
+
+/// Calendar period a budget resets after, in local time. Weeks start on
+/// Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A dollar cap on spend per period, optionally only for some projects or
+/// one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    pub period: BudgetPeriod,
+    pub amount_usd: f64,
+    /// Glob pattern on project paths, or on aliases for aliased projects
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Model family such as "opus", or a full model ID
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Spend against one budget in its current period
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    pub period_start: String,
+    pub period_end: String,
+    pub spent_usd: f64,
+    /// Zero once the budget is used up
+    pub remaining_usd: f64,
+    /// `spent_usd / amount_usd`
+    pub utilization: f64,
+    /// Spend at the end of the period if it continues at the rate so far
+    pub projected_usd: f64,
+    /// How far `projected_usd` exceeds the budget, absent when it doesn't
+    pub projected_overrun_usd: Option<f64>,
+}
+
+/// A short name for the projects whose paths match any of `paths`, e.g. all
+/// repos and worktrees of one client
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectAlias {
+    pub alias: String,
+    /// Glob patterns on project paths
+    pub paths: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+/// User-configurable settings. Missing fields take their value from
+/// `AppSettings::default()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
+    pub schema_version: u32,
     pub refresh_interval_secs: u64,
     pub window_hours: f64,
+    /// Overrides the plan's window limit when set
     pub usage_limit_tokens: Option<u64>,
     pub theme: String,
+    pub plan: Plan,
+    pub custom_limits: PlanLimits,
+    /// Weekly token caps by model family, overriding the plan's model caps
+    pub weekly_family_caps: BTreeMap<String, u64>,
+    /// Include a usage heatmap over this many days in every snapshot
+    pub heatmap_days: Option<u32>,
+    /// Gaps between responses longer than this don't count as active time
+    pub idle_threshold_mins: u32,
+    /// Shared directory of per-user usage exports for the team report
+    pub team_dir: Option<String>,
+    /// Globs on project paths; when any are set, only matching projects
+    /// are shown
+    pub include_projects: Vec<String>,
+    /// Globs on project paths that are never shown, even if included
+    pub exclude_projects: Vec<String>,
+    /// Replace project paths and session IDs with stable hashes everywhere
+    pub anonymize: bool,
+    /// Matched in order, the first alias with a matching path wins
+    pub project_aliases: Vec<ProjectAlias>,
+    pub budgets: Vec<Budget>,
+    /// Currency to show costs in besides USD, e.g. "EUR"; needs a rate in
+    /// `exchange_rates`
+    pub display_currency: Option<String>,
+    /// Units of each currency per US dollar, maintained by the user
+    pub exchange_rates: BTreeMap<String, f64>,
+    /// Date the exchange rates are from, as YYYY-MM-DD
+    pub exchange_rates_as_of: Option<String>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            schema_version: crate::settings::SCHEMA_VERSION,
             refresh_interval_secs: 180,
             window_hours: 5.0,
             usage_limit_tokens: None,
             theme: "system".to_string(),
+            plan: Plan::Custom,
+            custom_limits: PlanLimits::default(),
+            weekly_family_caps: BTreeMap::new(),
+            heatmap_days: None,
+            idle_threshold_mins: 15,
+            team_dir: None,
+            include_projects: vec![],
+            exclude_projects: vec![],
+            anonymize: false,
+            project_aliases: vec![],
+            budgets: vec![],
+            display_currency: None,
+            exchange_rates: BTreeMap::new(),
+            exchange_rates_as_of: None,
         }
     }
 }
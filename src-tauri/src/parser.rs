@@ -1,137 +1,687 @@
-use std::collections::HashSet;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::BufRead;
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use std::time::SystemTime;
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
 
-use crate::models::TokenUsage;
+use crate::archive::{
+    for_each_archived_transcript, is_archive, open_transcript, ARCHIVE_SUFFIXES,
+    TRANSCRIPT_SUFFIXES,
+};
+use crate::models::{ParseDiagnostics, SkipCount, SkipLocation, SkipReason, TokenUsage};
+use crate::privacy::Privacy;
 
-/// A single parsed usage entry from a JSONL file
+/// A single parsed usage entry from a JSONL file or a usage export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UsageEntry {
     pub model: String,
     pub usage: TokenUsage,
     pub timestamp: DateTime<Utc>,
     pub session_id: String,
+    /// Working directory of the session, or the encoded project directory
+    /// name when the line doesn't record one
+    pub project: String,
+    /// Names of the tools invoked by this response, one per `tool_use` block
+    pub tools: Vec<String>,
+    /// Checked-out git branch, empty outside a repository
+    pub git_branch: String,
+    /// Claude Code version that wrote the line
+    pub version: String,
+    pub request_id: String,
+    /// The API message ID, which together with `request_id` identifies a
+    /// response across files
+    pub message_id: String,
+    /// Machine an imported entry was recorded on, empty for this one
+    #[serde(skip)]
+    pub machine: String,
 }
 
-/// Parse a single JSONL file, returning deduplicated usage entries.
-/// Entries are deduplicated by `requestId` since the same API response
-/// produces multiple JSONL lines (thinking, text, tool_use content blocks)
-/// with identical usage data.
-pub fn parse_session_file(path: &Path) -> Vec<UsageEntry> {
-    let file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return vec![],
+/// A synthetic "usage limit reached" message Claude Code records when the
+/// API throttles us
+#[derive(Debug, Clone)]
+pub struct LimitHit {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Working directory of the session, as for `UsageEntry::project`
+    pub project: String,
+    /// Reset time, when the message carries one (`...limit reached|<epoch>`)
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+/// A prompt typed by the user, as opposed to tool results and other
+/// `user` lines Claude Code writes on our behalf
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserPrompt {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Working directory of the session, as for `UsageEntry::project`
+    pub project: String,
+    /// Line UUID, kept when a resumed session copies the prompt
+    pub uuid: String,
+}
+
+/// Everything extracted from one or more session files
+#[derive(Default)]
+pub struct ParsedUsage {
+    pub entries: Vec<UsageEntry>,
+    pub limit_hits: Vec<LimitHit>,
+    pub prompts: Vec<UserPrompt>,
+    pub diagnostics: ParseDiagnostics,
+}
+
+/// Line types that carry no usage and are skipped without being reported
+const IGNORED_TYPES: &[&str] = &[
+    "system",
+    "summary",
+    "progress",
+    "file-history-snapshot",
+    "queue-operation",
+];
+
+/// Sample locations kept per skip reason
+const MAX_SKIP_SAMPLES: usize = 5;
+
+impl ParseDiagnostics {
+    fn skip_count(&mut self, reason: SkipReason) -> &mut SkipCount {
+        let index = match self.skipped.iter().position(|s| s.reason == reason) {
+            Some(i) => i,
+            None => {
+                self.skipped.push(SkipCount {
+                    reason,
+                    count: 0,
+                    samples: vec![],
+                });
+                self.skipped.sort_by_key(|s| s.reason);
+                self.skipped
+                    .iter()
+                    .position(|s| s.reason == reason)
+                    .unwrap()
+            }
+        };
+        &mut self.skipped[index]
+    }
+
+    /// Count a skipped line; `line` is 1-based, or 0 for the whole file
+    fn skip(&mut self, reason: SkipReason, path: &Path, line: usize) {
+        let count = self.skip_count(reason);
+        count.count += 1;
+        if count.samples.len() < MAX_SKIP_SAMPLES {
+            count.samples.push(SkipLocation {
+                file: path.display().to_string(),
+                line,
+            });
+        }
+    }
+
+    fn merge(&mut self, other: ParseDiagnostics) {
+        self.files_scanned += other.files_scanned;
+        self.lines_read += other.lines_read;
+        self.entries_parsed += other.entries_parsed;
+        self.duplicate_lines += other.duplicate_lines;
+        self.cross_file_duplicates += other.cross_file_duplicates;
+        for skipped in other.skipped {
+            let count = self.skip_count(skipped.reason);
+            count.count += skipped.count;
+            let room = MAX_SKIP_SAMPLES.saturating_sub(count.samples.len());
+            count.samples.extend(skipped.samples.into_iter().take(room));
+        }
+    }
+}
+
+impl ParsedUsage {
+    pub fn skip(&mut self, reason: SkipReason, path: &Path, line: usize) {
+        self.diagnostics.skip(reason, path, line);
+    }
+
+    fn append(&mut self, other: ParsedUsage) {
+        self.entries.extend(other.entries);
+        self.limit_hits.extend(other.limit_hits);
+        self.prompts.extend(other.prompts);
+        self.diagnostics.merge(other.diagnostics);
+    }
+}
+
+/// Model name Claude Code uses for messages it generates itself rather
+/// than receiving from the API
+const SYNTHETIC_MODEL: &str = "<synthetic>";
+
+/// Recognize a synthetic rate-limit message, returning its reset time if any.
+/// Older versions write `Claude AI usage limit reached|<epoch secs>`, newer
+/// ones a human-readable `5-hour limit reached ∙ resets 3pm`.
+fn parse_limit_message(message: &serde_json::Value) -> Option<Option<DateTime<Utc>>> {
+    let text = match message.get("content") {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return None,
     };
-    let reader = BufReader::new(file);
-    let mut seen_requests: HashSet<String> = HashSet::new();
-    let mut entries = Vec::new();
+    if !text.to_lowercase().contains("limit reached") {
+        return None;
+    }
+    let resets_at = text
+        .split_once('|')
+        .and_then(|(_, epoch)| epoch.trim().parse::<i64>().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0));
+    Some(resets_at)
+}
 
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
+/// The parts of a transcript line we use. Everything else, notably tool
+/// inputs and results, is skipped by the deserializer without allocating.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Line<'a> {
+    #[serde(rename = "type", borrow)]
+    kind: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    timestamp: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    session_id: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    request_id: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    uuid: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    cwd: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    git_branch: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    version: Option<Cow<'a, str>>,
+    is_meta: Option<bool>,
+    is_sidechain: Option<bool>,
+    is_compact_summary: Option<bool>,
+    #[serde(borrow)]
+    message: Option<Message<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Message<'a> {
+    #[serde(borrow)]
+    id: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    model: Option<Cow<'a, str>>,
+    /// None when missing, Some(None) when present but malformed
+    #[serde(default, deserialize_with = "lenient_usage")]
+    usage: Option<Option<TokenUsage>>,
+    #[serde(borrow)]
+    content: Option<Content<'a>>,
+}
+
+/// Just the line type, for lines that fail the pre-filter
+#[derive(Deserialize)]
+struct LineType<'a> {
+    #[serde(rename = "type", borrow)]
+    kind: Option<Cow<'a, str>>,
+}
+
+/// A message's `content`: plain text for typed prompts, otherwise blocks
+enum Content<'a> {
+    /// Whether the text was typed, see `is_typed_text`
+    Text(bool),
+    Blocks(Vec<Block<'a>>),
+}
+
+#[derive(Deserialize)]
+struct Block<'a> {
+    #[serde(rename = "type", default, borrow, deserialize_with = "loose_str")]
+    kind: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "loose_str")]
+    id: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "loose_str")]
+    name: Option<Cow<'a, str>>,
+    /// Whether a `text` block's text was typed
+    #[serde(rename = "text", default, deserialize_with = "typed_text")]
+    typed: bool,
+}
+
+/// The shapes of a JSON value that content is read from. Anything else is
+/// `Other`, so an odd block or field is ignored instead of failing the line.
+enum Loose<'a> {
+    Str(Cow<'a, str>),
+    Object(Block<'a>),
+    Array(Vec<Loose<'a>>),
+    Other,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Loose<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LooseVisitor<'a>(PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for LooseVisitor<'a> {
+            type Value = Loose<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, text: &'de str) -> Result<Self::Value, E> {
+                Ok(Loose::Str(Cow::Borrowed(text)))
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                Ok(Loose::Str(Cow::Owned(text.to_string())))
+            }
+
+            fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+                Ok(Loose::Other)
+            }
+
+            fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+                Ok(Loose::Other)
+            }
+
+            fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+                Ok(Loose::Other)
+            }
+
+            fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+                Ok(Loose::Other)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Loose::Other)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Loose::Array(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Block::deserialize(MapAccessDeserializer::new(map)).map(Loose::Object)
+            }
+        }
+
+        deserializer.deserialize_any(LooseVisitor(PhantomData))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Content<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Loose::deserialize(deserializer)? {
+            Loose::Str(text) => Content::Text(is_typed_text(&text)),
+            Loose::Array(items) => Content::Blocks(
+                items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        Loose::Object(block) => Some(block),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            Loose::Object(_) | Loose::Other => Content::Blocks(vec![]),
+        })
+    }
+}
+
+/// Text the client writes into `user` lines itself: interrupt notices, and
+/// slash commands with their output
+const SYNTHETIC_PROMPTS: &[&str] = &[
+    "[Request interrupted by user",
+    "<command-name>",
+    "<command-message>",
+    "<local-command-stdout>",
+    "<local-command-stderr>",
+];
+
+/// Whether prompt text was typed: not blank and not synthetic
+fn is_typed_text(text: &str) -> bool {
+    let text = text.trim_start();
+    !text.is_empty() && !SYNTHETIC_PROMPTS.iter().any(|s| text.starts_with(s))
+}
+
+fn typed_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match Loose::deserialize(deserializer)? {
+        Loose::Str(text) => is_typed_text(&text),
+        _ => false,
+    })
+}
+
+/// A string field, left out when it holds anything else
+fn loose_str<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error> {
+    Ok(match Loose::deserialize(deserializer)? {
+        Loose::Str(text) => Some(text),
+        _ => None,
+    })
+}
+
+/// Keep a malformed `usage` object from failing the whole line, so it can
+/// be reported as such
+fn lenient_usage<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Option<TokenUsage>>, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(Some(serde_json::from_value(value).ok()))
+}
+
+/// Cheap check before a full parse: Claude Code writes compact JSON, so an
+/// assistant response or a prompt contains one of these. Nested objects
+/// match too, so a hit still has its real type checked, and a miss has its
+/// type read before the line is skipped, for JSON written with spaces.
+fn may_have_usage(line: &str) -> bool {
+    line.contains(r#""type":"assistant""#) || line.contains(r#""type":"user""#)
+}
+
+fn owned(field: Option<Cow<'_, str>>) -> String {
+    field.map(Cow::into_owned).unwrap_or_default()
+}
+
+impl Content<'_> {
+    /// `(id, name)` of every `tool_use` block
+    fn tool_uses(&self) -> Vec<(&str, &str)> {
+        match self {
+            Content::Text(_) => vec![],
+            Content::Blocks(blocks) => blocks
+                .iter()
+                .filter(|b| b.kind.as_deref() == Some("tool_use"))
+                .filter_map(|b| Some((b.id.as_deref().unwrap_or(""), b.name.as_deref()?)))
+                .collect(),
+        }
+    }
+
+    /// Typed text, as opposed to tool results
+    fn is_typed(&self) -> bool {
+        match self {
+            Content::Text(non_empty) => *non_empty,
+            Content::Blocks(blocks) => blocks
+                .iter()
+                .any(|b| b.kind.as_deref() == Some("text") && b.typed),
+        }
+    }
+}
+
+/// Recognize a `user` line the user actually typed. Tool results, meta
+/// messages, compaction summaries, subagent (sidechain) prompts, interrupt
+/// notices and slash commands are also recorded as `user` lines but aren't
+/// prompts.
+fn parse_prompt(line: &Line, project_dir: &str) -> Option<UserPrompt> {
+    let flag = |f: Option<bool>| f == Some(true);
+    if flag(line.is_meta) || flag(line.is_sidechain) || flag(line.is_compact_summary) {
+        return None;
+    }
+    if !line.message.as_ref()?.content.as_ref()?.is_typed() {
+        return None;
+    }
+    Some(UserPrompt {
+        timestamp: line.timestamp.as_deref()?.parse().ok()?,
+        session_id: line.session_id.as_deref().unwrap_or("").to_string(),
+        project: line.cwd.as_deref().unwrap_or(project_dir).to_string(),
+        uuid: line.uuid.as_deref().unwrap_or("").to_string(),
+    })
+}
+
+/// Parse a single JSONL file, returning deduplicated usage entries and any
+/// rate-limit hits. Entries are deduplicated by `requestId` since the same
+/// API response produces multiple JSONL lines (thinking, text, tool_use
+/// content blocks) with identical usage data; tool calls from all of those
+/// lines are merged into the one entry. Synthetic messages are not API
+/// responses and never count as usage. Lines that can't be used are counted
+/// by reason in `ParsedUsage::diagnostics`. Gzip and zstd compressed files
+/// are decompressed on the fly, and tar archives are read member by member.
+pub fn parse_session_file(path: &Path) -> ParsedUsage {
+    if is_archive(path) {
+        return parse_archive(path);
+    }
+    match open_transcript(path) {
+        Ok(reader) => parse_transcript(reader, path, &project_dir_name(path)),
+        Err(_) => {
+            let mut parsed = ParsedUsage::default();
+            parsed.diagnostics.files_scanned = 1;
+            parsed.skip(SkipReason::Io, path, 0);
+            parsed
+        }
+    }
+}
+
+/// Name of the project directory a transcript sits in
+fn project_dir_name(path: &Path) -> String {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Parse every transcript in a tar archive of project directories. Skipped
+/// lines are reported at the member's path inside the archive.
+fn parse_archive(path: &Path) -> ParsedUsage {
+    let mut parsed = ParsedUsage::default();
+    let result = for_each_archived_transcript(path, |member, reader| {
+        let project_dir = project_dir_name(member);
+        parsed.append(parse_transcript(reader, &path.join(member), &project_dir));
+    });
+    if result.is_err() {
+        parsed.skip(SkipReason::Io, path, 0);
+    }
+    parsed
+}
+
+/// Parse one transcript read from `path`; see `parse_session_file`
+fn parse_transcript(mut reader: impl BufRead, path: &Path, project_dir: &str) -> ParsedUsage {
+    let mut parsed = ParsedUsage::default();
+    parsed.diagnostics.files_scanned = 1;
+    // requestId -> index into `parsed.entries`
+    let mut seen_requests: HashMap<String, usize> = HashMap::new();
+    let mut seen_tool_ids: HashSet<String> = HashSet::new();
+    let mut seen_prompts: HashSet<String> = HashSet::new();
+
+    let mut buf = Vec::new();
+    let mut line_number = 0;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => line_number += 1,
+            Err(_) => {
+                parsed.skip(SkipReason::Io, path, line_number + 1);
+                break;
+            }
+        }
+        let line = match std::str::from_utf8(&buf) {
+            Ok(l) => l.trim(),
+            Err(_) => {
+                parsed.skip(SkipReason::Io, path, line_number);
+                continue;
+            }
         };
-        if line.trim().is_empty() {
+        if line.is_empty() {
             continue;
         }
+        parsed.diagnostics.lines_read += 1;
+
+        if !may_have_usage(line) {
+            match serde_json::from_str::<LineType>(line) {
+                Ok(LineType { kind }) if matches!(kind.as_deref(), Some("assistant" | "user")) => {}
+                Ok(LineType { kind }) => {
+                    if !kind.is_some_and(|t| IGNORED_TYPES.contains(&t.as_ref())) {
+                        parsed.skip(SkipReason::UnknownType, path, line_number);
+                    }
+                    continue;
+                }
+                Err(_) => {
+                    parsed.skip(SkipReason::InvalidJson, path, line_number);
+                    continue;
+                }
+            }
+        }
 
-        let raw: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(_) => continue,
+        let raw: Line = match serde_json::from_str(line) {
+            Ok(l) => l,
+            Err(e) => {
+                let reason = if e.is_data() {
+                    SkipReason::UnexpectedShape
+                } else {
+                    SkipReason::InvalidJson
+                };
+                parsed.skip(reason, path, line_number);
+                continue;
+            }
         };
 
-        // Only process assistant entries with usage data
-        let entry_type = raw.get("type").and_then(|v| v.as_str());
+        let entry_type = raw.kind.as_deref();
+        if entry_type == Some("user") {
+            let uuid = raw.uuid.as_deref().unwrap_or("");
+            if uuid.is_empty() || seen_prompts.insert(uuid.to_string()) {
+                parsed.prompts.extend(parse_prompt(&raw, project_dir));
+            }
+            continue;
+        }
+
+        // Otherwise only process assistant entries with usage data
         if entry_type != Some("assistant") {
+            if !entry_type.is_some_and(|t| IGNORED_TYPES.contains(&t)) {
+                parsed.skip(SkipReason::UnknownType, path, line_number);
+            }
             continue;
         }
 
-        let message = match raw.get("message") {
+        let message = match raw.message {
             Some(m) => m,
-            None => continue,
+            None => {
+                parsed.skip(SkipReason::MissingMessage, path, line_number);
+                continue;
+            }
         };
 
-        let usage_val = match message.get("usage") {
-            Some(u) => u,
-            None => continue,
+        let timestamp = match raw
+            .timestamp
+            .as_deref()
+            .and_then(|t| t.parse::<DateTime<Utc>>().ok())
+        {
+            Some(t) => t,
+            None => {
+                parsed.skip(SkipReason::InvalidTimestamp, path, line_number);
+                continue;
+            }
         };
 
-        let model = match message.get("model").and_then(|v| v.as_str()) {
-            Some(m) => m.to_string(),
-            None => continue,
-        };
+        let session_id = raw.session_id.as_deref().unwrap_or("").to_string();
 
-        // Deduplicate by requestId
-        let request_id = raw
-            .get("requestId")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        let model = match message.model.as_deref() {
+            Some(m) => m,
+            None => {
+                parsed.skip(SkipReason::MissingModel, path, line_number);
+                continue;
+            }
+        };
 
-        if !request_id.is_empty() && seen_requests.contains(&request_id) {
+        if model == SYNTHETIC_MODEL {
+            // Rare enough that the full tree is fine for reading the text
+            let limit = serde_json::from_str::<serde_json::Value>(line)
+                .ok()
+                .and_then(|v| v.get("message").and_then(parse_limit_message));
+            if let Some(resets_at) = limit {
+                parsed.limit_hits.push(LimitHit {
+                    timestamp,
+                    session_id,
+                    project: raw.cwd.as_deref().unwrap_or(project_dir).to_string(),
+                    resets_at,
+                });
+            }
             continue;
         }
-        if !request_id.is_empty() {
-            seen_requests.insert(request_id);
-        }
 
-        let usage: TokenUsage = match serde_json::from_value(usage_val.clone()) {
-            Ok(u) => u,
-            Err(_) => continue,
+        let usage = match message.usage {
+            Some(u) => u,
+            None => {
+                parsed.skip(SkipReason::MissingUsage, path, line_number);
+                continue;
+            }
         };
 
-        let timestamp_str = match raw.get("timestamp").and_then(|v| v.as_str()) {
-            Some(t) => t,
-            None => continue,
-        };
-        let timestamp = match timestamp_str.parse::<DateTime<Utc>>() {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
+        let tools: Vec<String> = message
+            .content
+            .as_ref()
+            .map(|c| c.tool_uses())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(id, _)| id.is_empty() || seen_tool_ids.insert(id.to_string()))
+            .map(|(_, name)| name.to_string())
+            .collect();
 
-        let session_id = raw
-            .get("sessionId")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        // Deduplicate by requestId
+        let request_id = raw.request_id.as_deref().unwrap_or("");
 
-        entries.push(UsageEntry {
-            model,
+        if let Some(&index) = seen_requests.get(request_id) {
+            parsed.entries[index].tools.extend(tools);
+            parsed.diagnostics.duplicate_lines += 1;
+            continue;
+        }
+
+        let usage: TokenUsage = match usage {
+            Some(u) => u,
+            None => {
+                parsed.skip(SkipReason::InvalidUsage, path, line_number);
+                continue;
+            }
+        };
+
+        if !request_id.is_empty() {
+            seen_requests.insert(request_id.to_string(), parsed.entries.len());
+        }
+        parsed.entries.push(UsageEntry {
+            model: model.to_string(),
             usage,
             timestamp,
             session_id,
+            project: raw.cwd.as_deref().unwrap_or(project_dir).to_string(),
+            tools,
+            git_branch: owned(raw.git_branch),
+            version: owned(raw.version),
+            request_id: request_id.to_string(),
+            message_id: owned(message.id),
+            machine: String::new(),
         });
     }
 
-    entries
+    parsed.diagnostics.entries_parsed = parsed.entries.len() as u64;
+    parsed
 }
 
-/// Discover all JSONL session files, optionally filtering by modification time.
+/// Discover the session transcripts under `projects_dir`, optionally
+/// filtering by modification time. Transcripts sit in one directory per
+/// project, plain or compressed; tar archives of whole project directories
+/// sit next to those directories.
 /// `min_mtime` allows skipping files that haven't been modified since a given time,
 /// which is critical for performance with ~300MB of session data.
-pub fn discover_session_files(min_mtime: Option<SystemTime>) -> Vec<PathBuf> {
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => return vec![],
-    };
-    let projects_dir = home.join(".claude").join("projects");
-
+pub fn discover_session_files(projects_dir: &Path, min_mtime: Option<SystemTime>) -> Vec<PathBuf> {
     if !projects_dir.exists() {
         return vec![];
     }
 
-    let pattern = projects_dir
-        .join("*")
-        .join("*.jsonl")
-        .to_string_lossy()
-        .to_string();
+    let transcripts = TRANSCRIPT_SUFFIXES
+        .iter()
+        .map(|suffix| projects_dir.join("*").join(format!("*{}", suffix)));
+    let archives = ARCHIVE_SUFFIXES
+        .iter()
+        .map(|suffix| projects_dir.join(format!("*{}", suffix)));
 
-    glob::glob(&pattern)
-        .unwrap_or_else(|_| glob::glob("").unwrap())
-        .filter_map(|entry| entry.ok())
+    transcripts
+        .chain(archives)
+        .flat_map(|pattern| {
+            glob::glob(&pattern.to_string_lossy())
+                .unwrap_or_else(|_| glob::glob("").unwrap())
+                .filter_map(|entry| entry.ok())
+        })
         .filter(|path| {
             if let Some(min) = min_mtime {
                 fs::metadata(path)
@@ -145,44 +695,123 @@ pub fn discover_session_files(min_mtime: Option<SystemTime>) -> Vec<PathBuf> {
         .collect()
 }
 
-/// Collect all usage entries from session files modified within the given window.
-pub fn collect_entries(window_hours: f64) -> Vec<UsageEntry> {
-    let min_mtime = SystemTime::now()
-        .checked_sub(std::time::Duration::from_secs(
-            (window_hours * 3600.0) as u64,
-        ))
-        .unwrap_or(SystemTime::UNIX_EPOCH);
-
-    // For weekly view we need up to 7 days of data
-    let weekly_mtime = SystemTime::now()
-        .checked_sub(std::time::Duration::from_secs(7 * 24 * 3600))
-        .unwrap_or(SystemTime::UNIX_EPOCH);
+/// Upper bound on parser threads. Parsing is mostly reading and scanning
+/// JSON, so more threads than this only add contention.
+const MAX_PARSE_WORKERS: usize = 8;
 
-    // Use the wider window so we have data for both views
-    let effective_mtime = std::cmp::min(min_mtime, weekly_mtime);
+/// Parse one file, keeping only what was recorded at or after `since` in
+/// projects `privacy` allows
+fn parse_session_file_since(path: &Path, since: DateTime<Utc>, privacy: &Privacy) -> ParsedUsage {
+    let mut parsed = parse_session_file(path);
+    parsed.entries.retain(|e| e.timestamp >= since);
+    parsed.limit_hits.retain(|h| h.timestamp >= since);
+    parsed.prompts.retain(|p| p.timestamp >= since);
+    privacy.filter(&mut parsed);
+    parsed
+}
 
-    let files = discover_session_files(Some(effective_mtime));
-    let mut all_entries = Vec::new();
+/// Parse `files` on a bounded pool of worker threads. Results come back in
+/// the order of `files` regardless of which worker finished first. Each
+/// worker drops out-of-range data before handing a file back through a
+/// bounded channel, so besides the results only about two files per worker
+/// are held in full at a time.
+fn parse_files_since(
+    files: &[PathBuf],
+    since: DateTime<Utc>,
+    privacy: &Privacy,
+) -> Vec<ParsedUsage> {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_PARSE_WORKERS)
+        .min(files.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<ParsedUsage>> = files.iter().map(|_| None).collect();
 
-    for file in files {
-        let file_entries = parse_session_file(&file);
-        // Only keep entries that fall within the weekly window at most
-        let weekly_cutoff = Utc::now() - Duration::days(7);
-        for entry in file_entries {
-            if entry.timestamp >= weekly_cutoff {
-                all_entries.push(entry);
-            }
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::sync_channel(workers);
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(index) else {
+                    return;
+                };
+                let parsed = parse_session_file_since(path, since, privacy);
+                if tx.send((index, parsed)).is_err() {
+                    return;
+                }
+            });
         }
+        drop(tx);
+        for (index, parsed) in rx {
+            results[index] = Some(parsed);
+        }
+    });
+
+    results.into_iter().flatten().collect()
+}
+
+/// Collect all usage entries and rate-limit hits recorded at or after `since`
+/// in projects `privacy` allows.
+pub fn collect_entries_since(
+    projects_dir: &Path,
+    since: DateTime<Utc>,
+    privacy: &Privacy,
+) -> ParsedUsage {
+    let min_mtime: SystemTime = since.into();
+    let files = discover_session_files(projects_dir, Some(min_mtime));
+    merge_sorted(parse_files_since(&files, since, privacy))
+}
+
+/// Drop responses already seen in another file, keeping the first. Entries
+/// with neither a request ID nor a message ID can't be matched and are kept.
+fn dedupe_across_files(entries: &mut Vec<UsageEntry>) -> u64 {
+    let mut seen = HashSet::new();
+    let keep: Vec<bool> = entries
+        .iter()
+        .map(|e| {
+            (e.request_id.is_empty() && e.message_id.is_empty())
+                || seen.insert((e.request_id.as_str(), e.message_id.as_str()))
+        })
+        .collect();
+    let before = entries.len();
+    let mut keep = keep.into_iter();
+    entries.retain(|_| keep.next().unwrap_or(true));
+    (before - entries.len()) as u64
+}
+
+/// Drop prompts already seen in another file, keeping the first. A resumed
+/// session copies its prompts along with the responses, under the same
+/// line UUID; prompts without one are kept.
+fn dedupe_prompts_across_files(prompts: &mut Vec<UserPrompt>) {
+    let mut seen = HashSet::new();
+    prompts.retain(|p| p.uuid.is_empty() || seen.insert(p.uuid.clone()));
+}
+
+/// Concatenate per-file (or per-machine) results, sort them by time and drop
+/// responses and prompts copied between them. The sort is stable, so ties keep
+/// input order and the output is deterministic.
+pub fn merge_sorted(per_file: Vec<ParsedUsage>) -> ParsedUsage {
+    let mut collected = ParsedUsage::default();
+    for parsed in per_file {
+        collected.append(parsed);
     }
 
-    all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-    all_entries
+    collected.entries.sort_by_key(|e| e.timestamp);
+    collected.limit_hits.sort_by_key(|h| h.timestamp);
+    collected.prompts.sort_by_key(|p| p.timestamp);
+    collected.diagnostics.cross_file_duplicates += dedupe_across_files(&mut collected.entries);
+    dedupe_prompts_across_files(&mut collected.prompts);
+    collected
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
+    use std::fs::File;
+    use std::io::{BufReader, Write};
     use tempfile::NamedTempFile;
 
     fn make_entry(request_id: &str, model: &str, output_tokens: u64) -> String {
@@ -199,7 +828,7 @@ mod tests {
         writeln!(file, "{}", make_entry("req_001", "claude-sonnet-4-5-20250929", 100)).unwrap();
         writeln!(file, "{}", make_entry("req_002", "claude-sonnet-4-5-20250929", 200)).unwrap();
 
-        let entries = parse_session_file(file.path());
+        let entries = parse_session_file(file.path()).entries;
         assert_eq!(entries.len(), 2);
         assert_eq!(entries[0].usage.output_tokens, 100);
         assert_eq!(entries[1].usage.output_tokens, 200);
@@ -212,7 +841,7 @@ mod tests {
         writeln!(file, r#"{{"type":"progress","timestamp":"2026-02-06T10:00:00Z"}}"#).unwrap();
         writeln!(file, r#"{{"type":"tool_use","timestamp":"2026-02-06T10:00:00Z"}}"#).unwrap();
 
-        let entries = parse_session_file(file.path());
+        let entries = parse_session_file(file.path()).entries;
         assert_eq!(entries.len(), 0);
     }
 
@@ -221,14 +850,65 @@ mod tests {
         let mut file = NamedTempFile::new().unwrap();
         writeln!(file, "not json at all").unwrap();
         writeln!(file, "{{broken json").unwrap();
-        writeln!(file, "").unwrap();
+        writeln!(file).unwrap();
         writeln!(file, "{}", make_entry("req_001", "claude-sonnet-4-5-20250929", 50)).unwrap();
 
-        let entries = parse_session_file(file.path());
+        let entries = parse_session_file(file.path()).entries;
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].usage.output_tokens, 50);
     }
 
+    #[test]
+    fn test_diagnostics_count_skip_reasons() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{{broken json").unwrap();
+        let entry = make_entry("req_001", "claude-sonnet-4-5-20250929", 50);
+        writeln!(file, "{}", entry).unwrap();
+        writeln!(file, "{}", entry).unwrap();
+        writeln!(file, r#"{{"type":"progress"}}"#).unwrap();
+        writeln!(file, r#"{{"type":"checkpoint"}}"#).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"yesterday","message":{{"model":"claude-sonnet-4-5-20250929"}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"2026-02-06T10:00:00Z","message":{{"model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":"many"}}}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":1770372000,"message":{{"model":"claude-sonnet-4-5-20250929"}}}}"#
+        )
+        .unwrap();
+
+        let diagnostics = parse_session_file(file.path()).diagnostics;
+        assert_eq!(diagnostics.files_scanned, 1);
+        assert_eq!(diagnostics.lines_read, 8);
+        assert_eq!(diagnostics.entries_parsed, 1);
+        assert_eq!(diagnostics.duplicate_lines, 1);
+        let skipped: Vec<(SkipReason, u64, usize)> = diagnostics
+            .skipped
+            .iter()
+            .map(|s| (s.reason, s.count, s.samples[0].line))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (SkipReason::InvalidJson, 1, 1),
+                (SkipReason::UnexpectedShape, 1, 8),
+                (SkipReason::UnknownType, 1, 5),
+                (SkipReason::InvalidTimestamp, 1, 6),
+                (SkipReason::InvalidUsage, 1, 7),
+            ]
+        );
+
+        let missing = parse_session_file(Path::new("/nonexistent/session.jsonl")).diagnostics;
+        assert_eq!(missing.skipped[0].reason, SkipReason::Io);
+        assert_eq!(missing.skipped[0].samples[0].line, 0);
+    }
+
     #[test]
     fn test_skips_entries_without_usage() {
         let mut file = NamedTempFile::new().unwrap();
@@ -238,7 +918,365 @@ mod tests {
         )
         .unwrap();
 
-        let entries = parse_session_file(file.path());
+        let entries = parse_session_file(file.path()).entries;
         assert_eq!(entries.len(), 0);
     }
+
+    #[test]
+    fn test_merges_tool_calls_across_request_lines() {
+        let line = |block: &str| {
+            format!(
+                r#"{{"type":"assistant","requestId":"req_001","timestamp":"2026-02-06T10:00:00Z","sessionId":"s1","message":{{"model":"claude-sonnet-4-5-20250929","content":[{}],"usage":{{"output_tokens":5}}}}}}"#,
+                block
+            )
+        };
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", line(r#"{"type":"text","text":"Let me look"}"#)).unwrap();
+        let bash = r#"{"type":"tool_use","id":"toolu_1","name":"Bash","input":{}}"#;
+        writeln!(file, "{}", line(bash)).unwrap();
+        // Exact duplicate line, e.g. copied into a resumed session
+        writeln!(file, "{}", line(bash)).unwrap();
+        let mcp =
+            r#"{"type":"tool_use","id":"toolu_2","name":"mcp__github__get_issue","input":{}}"#;
+        writeln!(file, "{}", line(mcp)).unwrap();
+
+        let entries = parse_session_file(file.path()).entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tools, vec!["Bash", "mcp__github__get_issue"]);
+    }
+
+    #[test]
+    fn test_reads_spaced_json_and_odd_blocks() {
+        let mut file = NamedTempFile::new().unwrap();
+        let spaced = make_entry("req_001", "claude-sonnet-4-5-20250929", 50)
+            .replace(r#""type":"assistant""#, r#""type": "assistant""#);
+        writeln!(file, "{}", spaced).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","requestId":"req_002","timestamp":"2026-02-06T10:00:00Z","message":{{"model":"claude-sonnet-4-5-20250929","content":["hi",{{"type":"tool_use","id":7,"name":{{"x":1}}}},{{"type":"tool_use","id":"toolu_1","name":"Bash"}}],"usage":{{"output_tokens":5}}}}}}"#
+        )
+        .unwrap();
+        writeln!(file, r#"{{"type": "progress"}}"#).unwrap();
+
+        let parsed = parse_session_file(file.path());
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].usage.output_tokens, 50);
+        assert_eq!(parsed.entries[1].tools, vec!["Bash"]);
+        assert!(parsed.diagnostics.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_counts_typed_prompts_only() {
+        let user = |uuid: &str, extra: &str, content: &str| {
+            format!(
+                r#"{{"type":"user","uuid":"{}","timestamp":"2026-02-06T10:00:00Z","sessionId":"s1"{},"message":{{"role":"user","content":{}}}}}"#,
+                uuid, extra, content
+            )
+        };
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", user("u1", "", r#""fix the tests""#)).unwrap();
+        // Same line again, e.g. after a resume
+        writeln!(file, "{}", user("u1", "", r#""fix the tests""#)).unwrap();
+        let text = r#"[{"type":"text","text":"now run clippy"}]"#;
+        writeln!(file, "{}", user("u2", "", text)).unwrap();
+        let result = r#"[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]"#;
+        writeln!(file, "{}", user("u3", "", result)).unwrap();
+        writeln!(file, "{}", user("u4", r#","isMeta":true"#, r#""caveat""#)).unwrap();
+        let subtask = user("u5", r#","isSidechain":true"#, r#""subtask""#);
+        writeln!(file, "{}", subtask).unwrap();
+        let interrupted = r#"[{"type":"text","text":"[Request interrupted by user]"}]"#;
+        writeln!(file, "{}", user("u6", "", interrupted)).unwrap();
+        let command = r#""<command-name>/clear</command-name>\n<command-args></command-args>""#;
+        writeln!(file, "{}", user("u7", "", command)).unwrap();
+        let output = r#""<local-command-stdout></local-command-stdout>""#;
+        writeln!(file, "{}", user("u8", "", output)).unwrap();
+
+        let parsed = parse_session_file(file.path());
+        assert_eq!(parsed.prompts.len(), 2);
+        assert!(parsed.entries.is_empty());
+    }
+
+    #[test]
+    fn test_project_branch_and_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let project_dir = dir.path().join("-Users-me-acme");
+        fs::create_dir(&project_dir).unwrap();
+        let path = project_dir.join("sess-1.jsonl");
+        let mut file = File::create(&path).unwrap();
+        writeln!(
+            file,
+            "{}",
+            make_entry("req_001", "claude-sonnet-4-5-20250929", 10)
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","requestId":"req_002","cwd":"/Users/me/acme","gitBranch":"feat/x","version":"2.0.14","timestamp":"2026-02-06T10:00:00Z","sessionId":"sess-1","message":{{"model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":5}}}}}}"#
+        )
+        .unwrap();
+
+        let entries = parse_session_file(&path).entries;
+        assert_eq!(entries[0].project, "-Users-me-acme");
+        assert_eq!(entries[1].project, "/Users/me/acme");
+        assert_eq!(entries[0].git_branch, "");
+        assert_eq!(entries[1].git_branch, "feat/x");
+        assert_eq!(entries[1].version, "2.0.14");
+    }
+
+    #[test]
+    fn test_records_limit_hits_instead_of_usage() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"2026-02-06T10:00:00Z","sessionId":"s1","message":{{"model":"<synthetic>","role":"assistant","content":[{{"type":"text","text":"Claude AI usage limit reached|1770386400"}}],"usage":{{"input_tokens":0,"output_tokens":0}}}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"2026-02-06T11:00:00Z","sessionId":"s1","message":{{"model":"<synthetic>","role":"assistant","content":[{{"type":"text","text":"5-hour limit reached ∙ resets 3pm"}}],"usage":{{"input_tokens":0,"output_tokens":0}}}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","timestamp":"2026-02-06T11:00:00Z","sessionId":"s1","message":{{"model":"<synthetic>","role":"assistant","content":[{{"type":"text","text":"No response requested."}}],"usage":{{"input_tokens":0,"output_tokens":0}}}}}}"#
+        )
+        .unwrap();
+
+        let parsed = parse_session_file(file.path());
+        assert_eq!(parsed.entries.len(), 0);
+        assert_eq!(parsed.limit_hits.len(), 2);
+        assert_eq!(
+            parsed.limit_hits[0].resets_at.unwrap().to_rfc3339(),
+            "2026-02-06T14:00:00+00:00"
+        );
+        assert!(parsed.limit_hits[1].resets_at.is_none());
+    }
+
+    #[test]
+    fn test_parallel_parse_keeps_file_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        let files: Vec<PathBuf> = (0..20)
+            .map(|i| {
+                let path = dir.path().join(format!("sess-{:02}.jsonl", i));
+                let mut file = File::create(&path).unwrap();
+                // Every file has a response at the same instant, so only
+                // file order separates them after sorting
+                writeln!(
+                    file,
+                    r#"{{"type":"assistant","requestId":"r{i}","timestamp":"2026-02-06T10:00:00Z","sessionId":"s{i}","message":{{"model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":{i}}}}}}}"#
+                )
+                .unwrap();
+                writeln!(
+                    file,
+                    r#"{{"type":"assistant","requestId":"old{i}","timestamp":"2026-02-01T10:00:00Z","sessionId":"s{i}","message":{{"model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":1}}}}}}"#
+                )
+                .unwrap();
+                path
+            })
+            .collect();
+
+        let collected = merge_sorted(parse_files_since(&files, t0, &Privacy::default()));
+        let sessions: Vec<String> = collected
+            .entries
+            .iter()
+            .map(|e| e.session_id.clone())
+            .collect();
+        let expected: Vec<String> = (0..20).map(|i| format!("s{}", i)).collect();
+        assert_eq!(sessions, expected);
+        assert_eq!(collected.diagnostics.files_scanned, 20);
+        assert!(parse_files_since(&[], t0, &Privacy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_dedupes_responses_copied_into_resumed_sessions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |session: &str, request: &str, message: &str| {
+            format!(
+                r#"{{"type":"assistant","requestId":"{}","timestamp":"2026-02-06T10:00:00Z","sessionId":"{}","message":{{"id":"{}","model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":10}}}}}}"#,
+                request, session, message
+            )
+        };
+        let prompt = |session: &str| {
+            format!(
+                r#"{{"type":"user","uuid":"u1","timestamp":"2026-02-06T09:59:00Z","sessionId":"{}","message":{{"role":"user","content":"fix the tests"}}}}"#,
+                session
+            )
+        };
+        let original = dir.path().join("original.jsonl");
+        let mut file = File::create(&original).unwrap();
+        writeln!(file, "{}", prompt("s1")).unwrap();
+        writeln!(file, "{}", line("s1", "req_1", "msg_1")).unwrap();
+        writeln!(file, "{}", line("s1", "req_2", "msg_2")).unwrap();
+        // `--resume` copies the history into a new session file
+        let resumed = dir.path().join("resumed.jsonl");
+        let mut file = File::create(&resumed).unwrap();
+        writeln!(file, "{}", prompt("s2")).unwrap();
+        writeln!(file, "{}", line("s2", "req_1", "msg_1")).unwrap();
+        writeln!(file, "{}", line("s2", "req_2", "msg_2")).unwrap();
+        writeln!(file, "{}", line("s2", "req_3", "msg_3")).unwrap();
+
+        let t0: DateTime<Utc> = "2026-02-06T00:00:00Z".parse().unwrap();
+        let collected = merge_sorted(parse_files_since(
+            &[original, resumed],
+            t0,
+            &Privacy::default(),
+        ));
+        let ids: Vec<&str> = collected
+            .entries
+            .iter()
+            .map(|e| e.request_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["req_1", "req_2", "req_3"]);
+        assert_eq!(collected.entries[0].session_id, "s1");
+        assert_eq!(collected.diagnostics.cross_file_duplicates, 2);
+        assert_eq!(collected.diagnostics.entries_parsed, 5);
+        assert_eq!(collected.prompts.len(), 1);
+        assert_eq!(collected.prompts[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_reads_compressed_and_archived_transcripts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |request: &str| {
+            format!(
+                r#"{{"type":"assistant","requestId":"{}","timestamp":"2026-02-06T10:00:00Z","sessionId":"s1","message":{{"model":"claude-sonnet-4-5-20250929","usage":{{"output_tokens":10}}}}}}"#,
+                request
+            )
+        };
+        let project_dir = dir.path().join("-Users-me-acme");
+        fs::create_dir(&project_dir).unwrap();
+        let gz = File::create(project_dir.join("s1.jsonl.gz")).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(gz, Default::default());
+        writeln!(encoder, "{}", line("req_1")).unwrap();
+        encoder.finish().unwrap();
+
+        // An archived project directory, next to the live ones
+        let tgz = File::create(dir.path().join("-Users-me-old.tgz")).unwrap();
+        let encoder = flate2::write::GzEncoder::new(tgz, Default::default());
+        let mut builder = tar::Builder::new(encoder);
+        let content = format!("{}\n{{broken\n", line("req_2"));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "-Users-me-old/s2.jsonl", content.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert_eq!(discover_session_files(dir.path(), None).len(), 2);
+        let t0: DateTime<Utc> = "2026-02-06T00:00:00Z".parse().unwrap();
+        let collected = collect_entries_since(dir.path(), t0, &Privacy::default());
+        let projects: Vec<&str> = collected
+            .entries
+            .iter()
+            .map(|e| e.project.as_str())
+            .collect();
+        assert_eq!(projects, vec!["-Users-me-acme", "-Users-me-old"]);
+        assert_eq!(collected.diagnostics.files_scanned, 2);
+        let invalid = &collected.diagnostics.skipped[0];
+        assert_eq!(invalid.reason, SkipReason::InvalidJson);
+        let archived = dir.path().join("-Users-me-old.tgz/-Users-me-old/s2.jsonl");
+        assert_eq!(invalid.samples[0].file, archived.display().to_string());
+        assert_eq!(invalid.samples[0].line, 2);
+    }
+
+    /// How transcripts used to be parsed: a full `Value` tree per line and a
+    /// re-deserialized copy of `usage`. Only kept as the benchmark baseline.
+    fn count_with_value_tree(path: &Path) -> usize {
+        let reader = BufReader::new(File::open(path).unwrap());
+        let mut seen = HashSet::new();
+        let mut count = 0;
+        for line in reader.lines() {
+            let raw: serde_json::Value = match serde_json::from_str(&line.unwrap()) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if raw.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+                continue;
+            }
+            let request_id = raw["requestId"].as_str().unwrap_or("").to_string();
+            let usage = match raw.get("message").and_then(|m| m.get("usage")) {
+                Some(u) => u,
+                None => continue,
+            };
+            if seen.insert(request_id)
+                && serde_json::from_value::<TokenUsage>(usage.clone()).is_ok()
+            {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Write a transcript of roughly `megabytes` with the usual mix of
+    /// prompts, thinking/text/tool_use responses, tool results and progress
+    fn write_corpus(path: &Path, megabytes: usize) {
+        let mut out = std::io::BufWriter::new(File::create(path).unwrap());
+        let prose = r#"Let me check the \"parser\" module.\n"#.repeat(100);
+        let tool_output = r#"    fn main() {}\n"#.repeat(500);
+        let mut written = 0;
+        let mut turn = 0;
+        while written < megabytes * 1_000_000 {
+            let head = format!(
+                r#""timestamp":"2026-02-06T10:00:00Z","sessionId":"s1","cwd":"/work/acme","gitBranch":"main","version":"2.0.14","uuid":"u{turn}""#
+            );
+            let usage = r#"{"input_tokens":10,"output_tokens":500,"cache_read_input_tokens":90000,"cache_creation_input_tokens":2000}"#;
+            let lines = [
+                format!(
+                    r#"{{"type":"user",{head},"message":{{"role":"user","content":"{prose}"}}}}"#
+                ),
+                format!(
+                    r#"{{"type":"assistant",{head},"requestId":"req_{turn}","message":{{"model":"claude-sonnet-4-5-20250929","content":[{{"type":"thinking","thinking":"{prose}"}}],"usage":{usage}}}}}"#
+                ),
+                format!(
+                    r#"{{"type":"assistant",{head},"requestId":"req_{turn}","message":{{"model":"claude-sonnet-4-5-20250929","content":[{{"type":"tool_use","id":"toolu_{turn}","name":"Read","input":{{"file_path":"/work/acme/src/main.rs"}}}}],"usage":{usage}}}}}"#
+                ),
+                format!(
+                    r#"{{"type":"user",{head},"message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"toolu_{turn}","content":"{tool_output}"}}]}}}}"#
+                ),
+                format!(r#"{{"type":"progress",{head},"data":{{"output":"{tool_output}"}}}}"#),
+            ];
+            for line in lines {
+                writeln!(out, "{}", line).unwrap();
+                written += line.len() + 1;
+            }
+            turn += 1;
+        }
+    }
+
+    /// Typed parsing against the old `Value` tree approach. Run with
+    /// `cargo test --release bench_parse -- --ignored --nocapture`; set
+    /// `CLAUDEPULSE_BENCH_MB` to change the corpus size.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_parse_large_corpus() {
+        let megabytes = std::env::var("CLAUDEPULSE_BENCH_MB")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("corpus.jsonl");
+        write_corpus(&path, megabytes);
+
+        let start = std::time::Instant::now();
+        let baseline = count_with_value_tree(&path);
+        let baseline_time = start.elapsed();
+        let start = std::time::Instant::now();
+        let parsed = parse_session_file(&path);
+        let typed_time = start.elapsed();
+
+        assert_eq!(parsed.entries.len(), baseline);
+        let throughput = |t: std::time::Duration| megabytes as f64 / t.as_secs_f64();
+        println!(
+            "{} MB: value tree {:.2?} ({:.0} MB/s), typed {:.2?} ({:.0} MB/s), {:.1}x faster",
+            megabytes,
+            baseline_time,
+            throughput(baseline_time),
+            typed_time,
+            throughput(typed_time),
+            baseline_time.as_secs_f64() / typed_time.as_secs_f64()
+        );
+    }
 }
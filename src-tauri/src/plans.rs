@@ -0,0 +1,133 @@
+use crate::models::{AppSettings, LimitUsage, ModelCap, Plan, PlanLimits};
+
+const MTOK: u64 = 1_000_000;
+
+/// Built-in plan presets. Anthropic doesn't publish token limits, so these
+/// are rough estimates over all tokens (input, output and cache); use the
+/// custom plan or `usage_limit_tokens` when you know your real numbers.
+pub fn preset_limits(plan: Plan) -> Option<PlanLimits> {
+    let opus_weekly = |tokens: u64| {
+        vec![ModelCap {
+            family: "opus".into(),
+            window_tokens: None,
+            weekly_tokens: Some(tokens),
+        }]
+    };
+    match plan {
+        Plan::Pro => Some(PlanLimits {
+            window_tokens: Some(20 * MTOK),
+            weekly_tokens: Some(200 * MTOK),
+            model_caps: vec![],
+        }),
+        Plan::Max5x => Some(PlanLimits {
+            window_tokens: Some(100 * MTOK),
+            weekly_tokens: Some(1_000 * MTOK),
+            model_caps: opus_weekly(400 * MTOK),
+        }),
+        Plan::Max20x => Some(PlanLimits {
+            window_tokens: Some(400 * MTOK),
+            weekly_tokens: Some(4_000 * MTOK),
+            model_caps: opus_weekly(1_600 * MTOK),
+        }),
+        Plan::Api => Some(PlanLimits::default()),
+        Plan::Custom => None,
+    }
+}
+
+/// Limits in effect for the given settings: the selected preset (or the
+/// custom limits), with `usage_limit_tokens` overriding the window limit and
+/// `weekly_family_caps` overriding per-family weekly caps.
+pub fn effective_limits(settings: &AppSettings) -> PlanLimits {
+    let mut limits = preset_limits(settings.plan).unwrap_or_else(|| settings.custom_limits.clone());
+    if settings.usage_limit_tokens.is_some() {
+        limits.window_tokens = settings.usage_limit_tokens;
+    }
+    for (family, tokens) in &settings.weekly_family_caps {
+        match limits
+            .model_caps
+            .iter_mut()
+            .find(|c| c.family.eq_ignore_ascii_case(family))
+        {
+            Some(cap) => cap.weekly_tokens = Some(*tokens),
+            None => limits.model_caps.push(ModelCap {
+                family: family.clone(),
+                window_tokens: None,
+                weekly_tokens: Some(*tokens),
+            }),
+        }
+    }
+    limits
+}
+
+pub fn limit_usage(used_tokens: u64, limit_tokens: Option<u64>) -> LimitUsage {
+    LimitUsage {
+        used_tokens,
+        limit_tokens,
+        utilization: limit_tokens
+            .filter(|l| *l > 0)
+            .map(|l| used_tokens as f64 / l as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_plan_uses_custom_limits() {
+        let settings = AppSettings {
+            custom_limits: PlanLimits {
+                window_tokens: Some(1_000),
+                weekly_tokens: Some(5_000),
+                model_caps: vec![],
+            },
+            ..Default::default()
+        };
+        let limits = effective_limits(&settings);
+        assert_eq!(limits.window_tokens, Some(1_000));
+        assert_eq!(limits.weekly_tokens, Some(5_000));
+    }
+
+    #[test]
+    fn test_usage_limit_overrides_preset_window() {
+        let settings = AppSettings {
+            plan: Plan::Max5x,
+            usage_limit_tokens: Some(42),
+            ..Default::default()
+        };
+        let limits = effective_limits(&settings);
+        assert_eq!(limits.window_tokens, Some(42));
+        assert_eq!(limits.weekly_tokens, Some(1_000 * MTOK));
+        assert_eq!(limits.model_caps[0].family, "opus");
+    }
+
+    #[test]
+    fn test_family_caps_override_and_extend_plan_caps() {
+        let settings = AppSettings {
+            plan: Plan::Max20x,
+            weekly_family_caps: [("opus".to_string(), 7), ("sonnet".to_string(), 9)].into(),
+            ..Default::default()
+        };
+        let limits = effective_limits(&settings);
+        assert_eq!(limits.model_caps.len(), 2);
+        assert_eq!(limits.model_caps[0].weekly_tokens, Some(7));
+        assert_eq!(limits.model_caps[1].family, "sonnet");
+        assert_eq!(limits.model_caps[1].weekly_tokens, Some(9));
+    }
+
+    #[test]
+    fn test_api_plan_has_no_limits() {
+        let settings = AppSettings {
+            plan: Plan::Api,
+            ..Default::default()
+        };
+        assert_eq!(effective_limits(&settings), PlanLimits::default());
+    }
+
+    #[test]
+    fn test_limit_usage() {
+        let usage = limit_usage(250, Some(1_000));
+        assert_eq!(usage.utilization, Some(0.25));
+        assert_eq!(limit_usage(250, None).utilization, None);
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+
+use glob::Pattern;
+
+use crate::aliases::ProjectAliases;
+use crate::models::AppSettings;
+use crate::parser::ParsedUsage;
+
+/// Which projects may appear in any output, what they're called, and
+/// whether project paths and session IDs are replaced by hashes. Filtering
+/// happens as transcripts are read; naming only when results are built, so
+/// budgets and imports see real project paths.
+#[derive(Debug, Clone, Default)]
+pub struct Privacy {
+    /// `None` shows every project. Include patterns that don't parse match
+    /// nothing.
+    include: Option<ProjectPatterns>,
+    exclude: ProjectPatterns,
+    /// Exclude patterns that don't parse. Nothing is shown while there are
+    /// any, since the projects they were meant to hide can't be told apart.
+    invalid_exclude: Vec<String>,
+    aliases: ProjectAliases,
+    /// Tags by the name an aliased project is shown under
+    shown_tags: BTreeMap<String, Vec<String>>,
+    anonymize: bool,
+}
+
+/// FNV-1a, whose output never changes between builds, unlike std's hasher
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A stable stand-in for `value` such as `project-8f14e45fceea167a`. Empty
+/// values stay empty.
+pub fn anonymize(kind: &str, value: &str) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+    format!("{}-{:016x}", kind, fnv1a(value))
+}
+
+/// Patterns that don't parse; settings validation rejects them, but loading
+/// keeps them so a typo never widens what's shown
+pub fn invalid_patterns(patterns: &[String]) -> Vec<&str> {
+    patterns
+        .iter()
+        .filter(|p| Pattern::new(p).is_err())
+        .map(String::as_str)
+        .collect()
+}
+
+fn compile(patterns: &[String]) -> Vec<Pattern> {
+    patterns
+        .iter()
+        .filter_map(|p| Pattern::new(p).ok())
+        .collect()
+}
+
+/// How Claude Code names a project's directory under `projects`: the path
+/// with everything but ASCII letters and digits replaced by `-`. Glob
+/// wildcards are kept so patterns can be converted too.
+fn encode_dir_name(path: &str) -> String {
+    path.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "*?[]!-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Glob patterns over project paths. Usage from sessions without a working
+/// directory is only known by its encoded directory name, such as
+/// `-Users-me-acme`, which is matched against the patterns encoded the same
+/// way. The encoding can't be reversed, since `-` also stands for `/`.
+#[derive(Debug, Clone, Default)]
+struct ProjectPatterns {
+    paths: Vec<Pattern>,
+    encoded: Vec<Pattern>,
+}
+
+impl ProjectPatterns {
+    fn new(patterns: &[String]) -> Self {
+        // Encoded names have no separators for `**` to cross, and it's only
+        // valid as a whole path component
+        let encoded: Vec<String> = patterns
+            .iter()
+            .map(|p| encode_dir_name(p).replace("**", "*"))
+            .collect();
+        Self {
+            paths: compile(patterns),
+            encoded: compile(&encoded),
+        }
+    }
+
+    fn matches(&self, project: &str) -> bool {
+        let patterns = if project.contains(['/', '\\']) {
+            &self.paths
+        } else {
+            &self.encoded
+        };
+        patterns.iter().any(|p| p.matches(project))
+    }
+}
+
+impl Privacy {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let aliases = ProjectAliases::new(&settings.project_aliases);
+        let shown = |kind, name: &str| {
+            if settings.anonymize {
+                anonymize(kind, name)
+            } else {
+                name.to_string()
+            }
+        };
+        let shown_tags = aliases
+            .tagged()
+            .map(|(alias, tags)| {
+                let tags = tags.iter().map(|t| shown("tag", t)).collect();
+                (shown("project", alias), tags)
+            })
+            .collect();
+        Self {
+            include: (!settings.include_projects.is_empty())
+                .then(|| ProjectPatterns::new(&settings.include_projects)),
+            exclude: ProjectPatterns::new(&settings.exclude_projects),
+            invalid_exclude: invalid_patterns(&settings.exclude_projects)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            aliases,
+            shown_tags,
+            anonymize: settings.anonymize,
+        }
+    }
+
+    /// Like `from_settings`, but an error while an exclude pattern is
+    /// invalid, for anything that shows or shares usage
+    pub fn checked(settings: &AppSettings) -> Result<Self, String> {
+        let privacy = Self::from_settings(settings);
+        match privacy.invalid_exclude.first() {
+            Some(pattern) => Err(format!(
+                "exclude_projects has an invalid glob pattern {:?}; fix it so excluded \
+                 projects stay hidden",
+                pattern
+            )),
+            None => Ok(privacy),
+        }
+    }
+
+    /// Whether project paths and session IDs are replaced by hashes
+    pub fn anonymizes(&self) -> bool {
+        self.anonymize
+    }
+
+    pub fn aliases(&self) -> &ProjectAliases {
+        &self.aliases
+    }
+
+    /// What the project at `path` is shown as: its alias or its path,
+    /// hashed when anonymizing since aliases name clients too
+    pub fn project_name(&self, path: &str) -> String {
+        let name = self.aliases.resolve(path).unwrap_or(path);
+        if self.anonymize {
+            anonymize("project", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Tags of a project by the name `project_name` gives it, hashed when
+    /// anonymizing
+    pub fn tags(&self, shown_project: &str) -> &[String] {
+        self.shown_tags
+            .get(shown_project)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether usage in `project` may be shown. With include patterns only
+    /// matching projects are; exclude patterns win over include ones.
+    pub fn allows(&self, project: &str) -> bool {
+        self.invalid_exclude.is_empty()
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.matches(project))
+            && !self.exclude.matches(project)
+    }
+
+    /// Drop usage and rate-limit hits in projects that aren't allowed
+    pub fn filter(&self, parsed: &mut ParsedUsage) {
+        parsed.entries.retain(|e| self.allows(&e.project));
+        parsed.prompts.retain(|p| self.allows(&p.project));
+        parsed.limit_hits.retain(|h| self.allows(&h.project));
+    }
+
+    /// Rename projects by alias and hash what identifies them if asked to.
+    /// Run once on filtered usage, right before building results from it.
+    pub fn display(&self, parsed: &mut ParsedUsage) {
+        for entry in &mut parsed.entries {
+            entry.project = self.project_name(&entry.project);
+        }
+        for prompt in &mut parsed.prompts {
+            prompt.project = self.project_name(&prompt.project);
+        }
+        for hit in &mut parsed.limit_hits {
+            hit.project = self.project_name(&hit.project);
+        }
+        if !self.anonymize {
+            return;
+        }
+        for entry in &mut parsed.entries {
+            entry.session_id = anonymize("session", &entry.session_id);
+        }
+        for prompt in &mut parsed.prompts {
+            prompt.session_id = anonymize("session", &prompt.session_id);
+        }
+        for hit in &mut parsed.limit_hits {
+            hit.session_id = anonymize("session", &hit.session_id);
+        }
+        // Transcript paths name the project they belong to
+        for skipped in &mut parsed.diagnostics.skipped {
+            for sample in &mut skipped.samples {
+                sample.file = anonymize("transcript", &sample.file);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProjectAlias;
+    use crate::parser::{LimitHit, UsageEntry};
+
+    fn entry(project: &str, session: &str) -> UsageEntry {
+        UsageEntry {
+            project: project.into(),
+            session_id: session.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filters_and_anonymizes() {
+        let settings = AppSettings {
+            include_projects: vec!["/work/**".into()],
+            exclude_projects: vec!["/work/clients/secret*".into()],
+            anonymize: true,
+            project_aliases: vec![ProjectAlias {
+                alias: "acme".into(),
+                paths: vec!["/work/acme-*".into()],
+                tags: vec!["clients".into()],
+            }],
+            ..Default::default()
+        };
+        let privacy = Privacy::from_settings(&settings);
+        assert!(privacy.allows("/work/acme"));
+        assert!(!privacy.allows("/work/clients/secret-app"));
+        assert!(!privacy.allows("/home/me/dotfiles"));
+
+        let mut parsed = ParsedUsage {
+            entries: vec![
+                entry("/work/acme", "s1"),
+                entry("/work/clients/secret-app", "s2"),
+                entry("/work/acme-api", "s3"),
+            ],
+            limit_hits: vec![LimitHit {
+                timestamp: Default::default(),
+                session_id: "s2".into(),
+                project: "/work/clients/secret-app".into(),
+                resets_at: None,
+            }],
+            ..Default::default()
+        };
+        privacy.filter(&mut parsed);
+        assert_eq!(parsed.entries.len(), 2);
+        assert!(parsed.limit_hits.is_empty());
+        // Filtering alone keeps real paths
+        assert_eq!(parsed.entries[1].project, "/work/acme-api");
+
+        privacy.display(&mut parsed);
+        assert_eq!(
+            parsed.entries[0].project,
+            anonymize("project", "/work/acme")
+        );
+        assert_eq!(parsed.entries[0].session_id, anonymize("session", "s1"));
+        // Aliases and their tags can name clients, so they're hashed too
+        let acme = anonymize("project", "acme");
+        assert_eq!(parsed.entries[1].project, acme);
+        assert_eq!(privacy.tags(&acme), [anonymize("tag", "clients")]);
+        // Stable across runs and builds
+        assert_eq!(anonymize("session", "s1"), "session-08d8ff07b578d149");
+        assert_eq!(anonymize("session", ""), "");
+    }
+
+    #[test]
+    fn test_encoded_dir_names_match_path_patterns() {
+        let settings = AppSettings {
+            include_projects: vec!["/Users/me/**".into()],
+            exclude_projects: vec!["/Users/me/acme*".into()],
+            ..Default::default()
+        };
+        let privacy = Privacy::from_settings(&settings);
+        assert!(!privacy.allows("-Users-me-acme"));
+        assert!(!privacy.allows("-Users-me-acme-api"));
+        assert!(privacy.allows("-Users-me-notes"));
+        assert!(!privacy.allows("-Users-other-notes"));
+
+        // Hits are filtered by their own project, even when the usage that
+        // shared their session was cut off by time before filtering
+        let mut parsed = ParsedUsage {
+            limit_hits: vec![LimitHit {
+                timestamp: Default::default(),
+                session_id: "s1".into(),
+                project: "-Users-me-acme".into(),
+                resets_at: None,
+            }],
+            ..Default::default()
+        };
+        privacy.filter(&mut parsed);
+        assert!(parsed.limit_hits.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_patterns_fail_closed() {
+        let settings = AppSettings {
+            include_projects: vec!["/work/[acme".into()],
+            ..Default::default()
+        };
+        let privacy = Privacy::from_settings(&settings);
+        assert!(!privacy.allows("/work/acme"));
+        assert!(Privacy::checked(&settings).is_ok());
+
+        let settings = AppSettings {
+            exclude_projects: vec!["/work/[client".into(), "/work/secret".into()],
+            ..Default::default()
+        };
+        assert!(!Privacy::from_settings(&settings).allows("/home/me/notes"));
+        assert!(Privacy::checked(&settings)
+            .unwrap_err()
+            .contains("/work/[client"));
+    }
+}
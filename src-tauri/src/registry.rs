@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::TokenUsage;
+
+/// Pricing per million tokens (USD)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_create: f64,
+}
+
+impl Pricing {
+    /// USD cost of a single response's token usage
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let mtok = 1_000_000.0;
+        (usage.input_tokens as f64 / mtok) * self.input
+            + (usage.output_tokens as f64 / mtok) * self.output
+            + (usage.cache_read_input_tokens as f64 / mtok) * self.cache_read
+            + (usage.cache_creation_input_tokens as f64 / mtok) * self.cache_create
+    }
+}
+
+/// Used for models with no registry entry in their family
+const DEFAULT_PRICING: Pricing = Pricing {
+    input: 3.00,
+    output: 15.00,
+    cache_read: 0.30,
+    cache_create: 3.75,
+};
+
+/// A registry entry for one family + version, e.g. opus 4.1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSpec {
+    pub family: String,
+    pub version: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    pub pricing: Pricing,
+}
+
+/// Name of the registry file in `~/.claude`
+pub const REGISTRY_FILE_NAME: &str = "claudepulse-models.json";
+
+/// User additions and overrides, read from `claudepulse-models.json`
+#[derive(Debug, Default, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    models: Vec<ModelSpec>,
+}
+
+/// What the registry knows about a model ID
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    /// "opus", "sonnet", "haiku", a user-defined family, or "other"
+    pub family: String,
+    /// Dotted version, e.g. "4.5"; empty when it couldn't be parsed
+    pub version: String,
+    pub date: Option<NaiveDate>,
+    pub display_name: String,
+    pub pricing: Pricing,
+    /// Whether the registry has an entry for this exact family and version.
+    /// Unrecognized models get a best-guess name and pricing.
+    pub recognized: bool,
+}
+
+const fn spec_pricing(input: f64, output: f64, cache_read: f64, cache_create: f64) -> Pricing {
+    Pricing {
+        input,
+        output,
+        cache_read,
+        cache_create,
+    }
+}
+
+/// (family, version, pricing)
+const BUILTIN: &[(&str, &str, Pricing)] = &[
+    ("opus", "4.6", spec_pricing(5.00, 25.00, 0.50, 6.25)),
+    ("opus", "4.5", spec_pricing(5.00, 25.00, 0.50, 6.25)),
+    ("opus", "4.1", spec_pricing(15.00, 75.00, 1.50, 18.75)),
+    ("opus", "4", spec_pricing(15.00, 75.00, 1.50, 18.75)),
+    ("opus", "3", spec_pricing(15.00, 75.00, 1.50, 18.75)),
+    ("sonnet", "4.5", spec_pricing(3.00, 15.00, 0.30, 3.75)),
+    ("sonnet", "4", spec_pricing(3.00, 15.00, 0.30, 3.75)),
+    ("sonnet", "3.7", spec_pricing(3.00, 15.00, 0.30, 3.75)),
+    ("sonnet", "3.5", spec_pricing(3.00, 15.00, 0.30, 3.75)),
+    ("haiku", "4.5", spec_pricing(1.00, 5.00, 0.10, 1.25)),
+    ("haiku", "3.5", spec_pricing(0.80, 4.00, 0.08, 1.00)),
+    ("haiku", "3", spec_pricing(0.25, 1.25, 0.03, 0.30)),
+];
+
+pub struct ModelRegistry {
+    specs: Vec<ModelSpec>,
+}
+
+fn version_parts(version: &str) -> Vec<u32> {
+    version.split('.').filter_map(|p| p.parse().ok()).collect()
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl ModelRegistry {
+    pub fn builtin() -> Self {
+        let specs = BUILTIN
+            .iter()
+            .map(|(family, version, pricing)| ModelSpec {
+                family: family.to_string(),
+                version: version.to_string(),
+                display_name: None,
+                pricing: *pricing,
+            })
+            .collect();
+        Self { specs }
+    }
+
+    /// Add the models in a user registry file, replacing built-in ones with
+    /// the same family and version. A missing or unreadable file leaves the
+    /// registry as is.
+    pub fn merge_file(&mut self, path: &Path) {
+        let file: RegistryFile = match fs::read_to_string(path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(f) => f,
+            None => return,
+        };
+        for spec in file.models {
+            self.specs.retain(|s| {
+                !(s.family.eq_ignore_ascii_case(&spec.family) && s.version == spec.version)
+            });
+            self.specs.push(spec);
+        }
+    }
+
+    /// Whether `token` names a family in the registry, in any case
+    pub fn is_family(&self, token: &str) -> bool {
+        self.specs
+            .iter()
+            .any(|s| s.family.eq_ignore_ascii_case(token))
+    }
+
+    /// Parse a model ID into family, version and release date. Handles both
+    /// `claude-<family>-<major>[-<minor>][-<date>]` and the older
+    /// `claude-<major>[-<minor>]-<family>[-<date>]` layouts, as well as
+    /// provider prefixes such as `anthropic.claude-...`.
+    fn parse(&self, model: &str) -> (Option<String>, String, Option<NaiveDate>) {
+        let rest = match model.find("claude-") {
+            Some(i) => &model[i + "claude-".len()..],
+            None => return (None, String::new(), None),
+        };
+        let mut family = None;
+        let mut version = Vec::new();
+        let mut date = None;
+
+        for token in rest.split(['-', '@']) {
+            let is_digits = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+            if family.is_none() && self.is_family(token) {
+                family = Some(token.to_lowercase());
+            } else if is_digits && token.len() <= 2 && date.is_none() {
+                version.push(token);
+            } else if is_digits && token.len() == 8 {
+                date = NaiveDate::parse_from_str(token, "%Y%m%d").ok();
+            }
+        }
+
+        (family, version.join("."), date)
+    }
+
+    pub fn resolve(&self, model: &str) -> ModelInfo {
+        let (family, version, date) = self.parse(model);
+        let family = match family {
+            Some(f) if !version.is_empty() => f,
+            _ => {
+                return ModelInfo {
+                    family: "other".into(),
+                    version,
+                    date,
+                    display_name: model.to_string(),
+                    pricing: DEFAULT_PRICING,
+                    recognized: false,
+                }
+            }
+        };
+
+        let in_family: Vec<&ModelSpec> = self
+            .specs
+            .iter()
+            .filter(|s| s.family.eq_ignore_ascii_case(&family))
+            .collect();
+        let exact = in_family.iter().find(|s| s.version == version);
+        // Unknown versions are priced like the closest sibling: same major
+        // version if there is one, otherwise the newest in the family
+        let parts = version_parts(&version);
+        let nearest = exact.or_else(|| {
+            in_family
+                .iter()
+                .filter(|s| version_parts(&s.version).first() == parts.first())
+                .max_by_key(|s| version_parts(&s.version))
+                .or_else(|| in_family.iter().max_by_key(|s| version_parts(&s.version)))
+        });
+
+        let display_name = exact
+            .and_then(|s| s.display_name.clone())
+            .unwrap_or_else(|| format!("{} {}", capitalize(&family), version));
+
+        ModelInfo {
+            pricing: nearest.map(|s| s.pricing).unwrap_or(DEFAULT_PRICING),
+            recognized: exact.is_some(),
+            family,
+            version,
+            date,
+            display_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parses_current_and_legacy_ids() {
+        let registry = ModelRegistry::builtin();
+
+        let info = registry.resolve("claude-opus-4-1-20250805");
+        assert_eq!(info.family, "opus");
+        assert_eq!(info.version, "4.1");
+        assert_eq!(info.date, NaiveDate::from_ymd_opt(2025, 8, 5));
+        assert_eq!(info.display_name, "Opus 4.1");
+
+        let info = registry.resolve("claude-3-5-haiku-20241022");
+        assert_eq!(info.family, "haiku");
+        assert_eq!(info.version, "3.5");
+        assert_eq!(info.display_name, "Haiku 3.5");
+
+        let info = registry.resolve("anthropic.claude-3-7-sonnet-20250219-v1:0");
+        assert_eq!(info.display_name, "Sonnet 3.7");
+        assert!(info.recognized);
+    }
+
+    #[test]
+    fn test_no_longer_mislabels_older_models() {
+        let registry = ModelRegistry::builtin();
+        assert_eq!(
+            registry.resolve("claude-opus-4-20250514").display_name,
+            "Opus 4"
+        );
+        assert_eq!(
+            registry.resolve("claude-sonnet-4-20250514").display_name,
+            "Sonnet 4"
+        );
+        assert_eq!(
+            registry.resolve("claude-opus-4-20250514").pricing.output,
+            75.00
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_models_are_flagged() {
+        let registry = ModelRegistry::builtin();
+
+        let info = registry.resolve("gpt-4");
+        assert!(!info.recognized);
+        assert_eq!(info.family, "other");
+        assert_eq!(info.display_name, "gpt-4");
+
+        // New version in a known family: named from the ID, priced like its
+        // nearest sibling, but still reported
+        let info = registry.resolve("claude-sonnet-4-7-20260301");
+        assert!(!info.recognized);
+        assert_eq!(info.display_name, "Sonnet 4.7");
+        assert_eq!(info.pricing.output, 15.00);
+    }
+
+    #[test]
+    fn test_user_file_overrides_and_extends() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("claudepulse-models.json");
+        fs::write(
+            &path,
+            r#"{"models":[
+                {"family":"opus","version":"5","display_name":"Opus Five",
+                 "pricing":{"input":4,"output":20,"cache_read":0.4,"cache_create":5}},
+                {"family":"sonnet","version":"4.5",
+                 "pricing":{"input":1,"output":2,"cache_read":0.1,"cache_create":1}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut registry = ModelRegistry::builtin();
+        registry.merge_file(&path);
+
+        let info = registry.resolve("claude-opus-5-20261001");
+        assert!(info.recognized);
+        assert_eq!(info.display_name, "Opus Five");
+        assert_eq!(info.pricing.output, 20.0);
+        assert_eq!(
+            registry
+                .resolve("claude-sonnet-4-5-20250929")
+                .pricing
+                .output,
+            2.0
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::SessionStats;
+use crate::parser::{UsageEntry, UserPrompt};
+use crate::registry::ModelRegistry;
+
+/// `value` per hour of `active_secs`, or None when there was no active time
+pub fn per_active_hour(value: f64, active_secs: u64) -> Option<f64> {
+    (active_secs > 0).then(|| value / (active_secs as f64 / 3600.0))
+}
+
+/// Duration and active time of every session with responses in
+/// `[start, end]`, most recent first. Active time sums the gaps between
+/// consecutive responses, leaving out gaps longer than `idle_threshold`.
+/// `entries` must be sorted by timestamp.
+pub fn session_stats(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    prompts: &[UserPrompt],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    idle_threshold: Duration,
+) -> Vec<SessionStats> {
+    let mut by_session: HashMap<&str, Vec<&UsageEntry>> = HashMap::new();
+    for entry in entries
+        .iter()
+        .filter(|e| e.timestamp >= start && e.timestamp <= end && !e.session_id.is_empty())
+    {
+        by_session.entry(&entry.session_id).or_default().push(entry);
+    }
+    let mut prompt_counts: HashMap<&str, u64> = HashMap::new();
+    for prompt in prompts
+        .iter()
+        .filter(|p| p.timestamp >= start && p.timestamp <= end)
+    {
+        *prompt_counts.entry(&prompt.session_id).or_default() += 1;
+    }
+
+    let mut sessions: Vec<SessionStats> = by_session
+        .into_iter()
+        .map(|(session_id, session)| {
+            let first = session[0].timestamp;
+            let last = session[session.len() - 1].timestamp;
+            let active_secs: i64 = session
+                .windows(2)
+                .map(|pair| pair[1].timestamp - pair[0].timestamp)
+                .filter(|gap| *gap <= idle_threshold)
+                .map(|gap| gap.num_seconds())
+                .sum();
+            let active_secs = active_secs.max(0) as u64;
+            let message_count = session.len() as u64;
+            let cost_usd: f64 = session
+                .iter()
+                .map(|e| registry.resolve(&e.model).pricing.cost(&e.usage))
+                .sum();
+
+            SessionStats {
+                session_id: session_id.to_string(),
+                project: session[0].project.clone(),
+                start: first.to_rfc3339(),
+                end: last.to_rfc3339(),
+                duration_secs: (last - first).num_seconds().max(0) as u64,
+                active_secs,
+                prompt_count: prompt_counts.get(session_id).copied().unwrap_or(0),
+                message_count,
+                tokens: session.iter().map(|e| e.usage.total()).sum(),
+                cost_usd,
+                messages_per_active_hour: per_active_hour(message_count as f64, active_secs),
+                cost_per_active_hour: per_active_hour(cost_usd, active_secs),
+            }
+        })
+        .collect();
+    sessions.sort_by(|a, b| {
+        b.end
+            .cmp(&a.end)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenUsage;
+
+    fn entry(minutes: i64, session: &str) -> UsageEntry {
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens: 100_000,
+                ..Default::default()
+            },
+            timestamp: t0 + Duration::minutes(minutes),
+            session_id: session.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let entries = vec![
+            entry(0, "s1"),
+            entry(5, "s1"),
+            entry(10, "s1"),
+            entry(12, "s2"),
+            // Back after lunch; the 60 minute gap isn't active time
+            entry(70, "s1"),
+            entry(80, "s1"),
+        ];
+        let start = entries[0].timestamp;
+        let end = start + Duration::hours(2);
+
+        let prompts = vec![UserPrompt {
+            timestamp: start,
+            session_id: "s1".into(),
+            ..Default::default()
+        }];
+
+        let sessions = session_stats(
+            &ModelRegistry::builtin(),
+            &entries,
+            &prompts,
+            start,
+            end,
+            Duration::minutes(15),
+        );
+
+        assert_eq!(sessions.len(), 2);
+        let s1 = &sessions[0];
+        assert_eq!(s1.session_id, "s1");
+        assert_eq!(s1.duration_secs, 80 * 60);
+        assert_eq!(s1.active_secs, 20 * 60);
+        assert_eq!(s1.prompt_count, 1);
+        assert_eq!(s1.message_count, 5);
+        assert_eq!(s1.messages_per_active_hour, Some(15.0));
+        // 0.5 MTok output at $15 over a third of an hour
+        assert!((s1.cost_per_active_hour.unwrap() - 22.5).abs() < 1e-9);
+
+        // A single response has no measurable active time
+        let s2 = &sessions[1];
+        assert_eq!(s2.active_secs, 0);
+        assert_eq!(s2.messages_per_active_hour, None);
+    }
+}
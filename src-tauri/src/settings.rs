@@ -1,25 +1,674 @@
+use crate::currency::exchange_rate;
 use crate::models::AppSettings;
-use std::fs;
-use std::path::PathBuf;
+use crate::privacy::invalid_patterns;
+use crate::registry::{ModelRegistry, REGISTRY_FILE_NAME};
+use chrono::{Local, NaiveDate};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-fn settings_path() -> PathBuf {
+/// Current on-disk settings schema version. Bump this and append a step to
+/// `MIGRATIONS` whenever the shape of `AppSettings` changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Migration steps, indexed by the version they upgrade *from*.
+/// `MIGRATIONS[0]` turns a v0 document into v1, and so on.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v0_to_v1];
+
+/// v0 files predate versioning; the field set is unchanged, only the
+/// version stamp is added.
+fn migrate_v0_to_v1(_doc: &mut Map<String, Value>) {}
+
+/// Longest heatmap range; wider ranges mean parsing more transcripts
+pub const MAX_HEATMAP_DAYS: u32 = 365;
+
+/// A single field that failed validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Invalid(Vec<FieldError>),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Invalid(errors) => {
+                let parts: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                write!(f, "Invalid settings: {}", parts.join("; "))
+            }
+            SettingsError::Io(e) => write!(f, "Settings IO error: {}", e),
+            SettingsError::Json(e) => write!(f, "Settings JSON error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        SettingsError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SettingsError {
+    fn from(e: serde_json::Error) -> Self {
+        SettingsError::Json(e)
+    }
+}
+
+pub fn settings_path() -> PathBuf {
     let home = dirs::home_dir().expect("No home dir");
     home.join(".claude").join("claudepulse-settings.json")
 }
 
+/// The registry model families in settings are checked against: the
+/// built-in models plus the user's registry file next to the settings file
+fn registry_beside(path: &Path) -> ModelRegistry {
+    let mut registry = ModelRegistry::builtin();
+    registry.merge_file(&path.with_file_name(REGISTRY_FILE_NAME));
+    registry
+}
+
+/// Check every field for values the app can't work with. Model families
+/// must be known to `registry`.
+pub fn validate(settings: &AppSettings, registry: &ModelRegistry) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut fail = |field: &'static str, message: &str| {
+        errors.push(FieldError {
+            field,
+            message: message.to_string(),
+        })
+    };
+
+    if settings.schema_version > SCHEMA_VERSION {
+        fail(
+            "schema_version",
+            "was written by a newer version of ClaudePulse",
+        );
+    }
+    if settings.refresh_interval_secs < 10 {
+        fail("refresh_interval_secs", "must be at least 10 seconds");
+    }
+    if !settings.window_hours.is_finite()
+        || settings.window_hours <= 0.0
+        || settings.window_hours > 24.0
+    {
+        fail("window_hours", "must be greater than 0 and at most 24");
+    }
+    if settings.usage_limit_tokens == Some(0) {
+        fail("usage_limit_tokens", "must be positive when set");
+    }
+    if !matches!(settings.theme.as_str(), "light" | "dark" | "system") {
+        fail("theme", "must be one of light, dark, system");
+    }
+    let limits = &settings.custom_limits;
+    let caps = limits
+        .model_caps
+        .iter()
+        .flat_map(|c| [c.window_tokens, c.weekly_tokens]);
+    if [limits.window_tokens, limits.weekly_tokens]
+        .into_iter()
+        .chain(caps)
+        .any(|l| l == Some(0))
+    {
+        fail("custom_limits", "limits must be positive when set");
+    }
+    // A cap on a family the registry doesn't know, say "opus-4", would
+    // never match any usage
+    if limits
+        .model_caps
+        .iter()
+        .any(|c| !registry.is_family(c.family.trim()))
+    {
+        fail("custom_limits", "model caps need a known model family");
+    }
+    if settings
+        .weekly_family_caps
+        .iter()
+        .any(|(family, tokens)| !registry.is_family(family.trim()) || *tokens == 0)
+    {
+        fail(
+            "weekly_family_caps",
+            "caps need a known model family and a positive limit",
+        );
+    }
+
+    if settings
+        .heatmap_days
+        .is_some_and(|d| d == 0 || d > MAX_HEATMAP_DAYS)
+    {
+        fail(
+            "heatmap_days",
+            &format!("must be between 1 and {} days", MAX_HEATMAP_DAYS),
+        );
+    }
+    if settings.idle_threshold_mins == 0 {
+        fail("idle_threshold_mins", "must be at least 1 minute");
+    }
+    if settings
+        .team_dir
+        .as_deref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        fail("team_dir", "must not be empty when set");
+    }
+    for (field, patterns) in [
+        ("include_projects", &settings.include_projects),
+        ("exclude_projects", &settings.exclude_projects),
+    ] {
+        let invalid = invalid_patterns(patterns);
+        if !invalid.is_empty() {
+            fail(field, &format!("invalid glob pattern {:?}", invalid[0]));
+        }
+    }
+    for budget in &settings.budgets {
+        if !(budget.amount_usd.is_finite() && budget.amount_usd > 0.0) {
+            fail("budgets", "amounts must be positive");
+        } else if budget.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            fail("budgets", "model must not be empty when set");
+        } else if let Some(pattern) = budget
+            .project
+            .as_deref()
+            .filter(|p| glob::Pattern::new(p).is_err())
+        {
+            fail("budgets", &format!("invalid glob pattern {:?}", pattern));
+        }
+    }
+    if settings
+        .exchange_rates
+        .iter()
+        .any(|(code, rate)| code.trim().is_empty() || !(rate.is_finite() && *rate > 0.0))
+    {
+        fail(
+            "exchange_rates",
+            "rates need a currency code and a positive rate",
+        );
+    }
+    if let Some(code) = &settings.display_currency {
+        let code = code.trim().to_uppercase();
+        if code.is_empty() {
+            fail("display_currency", "must not be empty when set");
+        } else if code != "USD" && exchange_rate(settings, &code).is_none() {
+            fail("display_currency", "needs a rate in exchange_rates");
+        }
+    }
+    if settings
+        .exchange_rates_as_of
+        .as_deref()
+        .is_some_and(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_err())
+    {
+        fail("exchange_rates_as_of", "must be a date like 2026-01-31");
+    }
+    for alias in &settings.project_aliases {
+        if alias.alias.trim().is_empty() || alias.tags.iter().any(|t| t.trim().is_empty()) {
+            fail("project_aliases", "aliases and tags must not be empty");
+        } else if let Some(pattern) = invalid_patterns(&alias.paths).first() {
+            fail(
+                "project_aliases",
+                &format!("invalid glob pattern {:?}", pattern),
+            );
+        }
+    }
+
+    errors
+}
+
+/// Bring a raw settings document up to `SCHEMA_VERSION`.
+/// Returns the version the document started at.
+fn migrate(doc: &mut Map<String, Value>) -> u32 {
+    let from = doc
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(doc);
+        doc.insert("schema_version".into(), Value::from(version as u32 + 1));
+    }
+    from
+}
+
+/// Fields kept as written even when invalid. Resetting a project filter to
+/// its default would show projects the user asked to hide, so `Privacy`
+/// fails closed on bad patterns instead.
+const KEPT_WHEN_INVALID: &[&str] = &["include_projects", "exclude_projects"];
+
+/// Build settings from a migrated document one field at a time, so a single
+/// bad value falls back to its default instead of discarding the whole file.
+/// Rejected fields are retried until no more are accepted, so a field that
+/// is validated against another one (`display_currency` needs its rate in
+/// `exchange_rates`) doesn't depend on the key order in the file.
+/// Returns the settings and the names of the fields that were rejected.
+fn merge_fields(doc: &Map<String, Value>, registry: &ModelRegistry) -> (AppSettings, Vec<String>) {
+    let mut merged = match serde_json::to_value(AppSettings::default()) {
+        Ok(Value::Object(m)) => m,
+        _ => return (AppSettings::default(), doc.keys().cloned().collect()),
+    };
+    let mut pending: Vec<(&String, &Value)> = doc.iter().collect();
+
+    loop {
+        let before = pending.len();
+        pending.retain(|(key, value)| {
+            let mut candidate = merged.clone();
+            candidate.insert(key.to_string(), (*value).clone());
+            let accepted = match serde_json::from_value::<AppSettings>(Value::Object(candidate)) {
+                Ok(parsed) => {
+                    KEPT_WHEN_INVALID.contains(&key.as_str())
+                        || !validate(&parsed, registry)
+                            .iter()
+                            .any(|e| e.field == key.as_str())
+                }
+                Err(_) => false,
+            };
+            if accepted {
+                merged.insert(key.to_string(), (*value).clone());
+            }
+            !accepted
+        });
+        if pending.len() == before {
+            break;
+        }
+    }
+
+    let rejected = pending.into_iter().map(|(key, _)| key.clone()).collect();
+    let settings = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    (settings, rejected)
+}
+
+/// Copy an unreadable or partially rejected settings file aside so the
+/// user's original content is never lost.
+fn backup(path: &Path) -> Option<PathBuf> {
+    let stamp = Local::now().format("%Y%m%dT%H%M%S");
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let backup_path = path.with_file_name(format!("{}.bak-{}", file_name, stamp));
+    fs::copy(path, &backup_path).ok()?;
+    Some(backup_path)
+}
+
+/// Load settings from `path`, migrating older schemas and falling back to
+/// per-field defaults. Anything that had to be discarded is backed up first.
+pub fn load_settings_from(path: &Path) -> AppSettings {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return AppSettings::default(),
+    };
+
+    let mut doc = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(m)) => m,
+        _ => {
+            if let Some(b) = backup(path) {
+                eprintln!("Unreadable settings file, backed up to {}", b.display());
+            }
+            return AppSettings::default();
+        }
+    };
+
+    let from_version = migrate(&mut doc);
+    if from_version > SCHEMA_VERSION {
+        if let Some(b) = backup(path) {
+            eprintln!(
+                "Settings schema v{} is newer than supported v{}, backed up to {}",
+                from_version,
+                SCHEMA_VERSION,
+                b.display()
+            );
+        }
+        return AppSettings::default();
+    }
+
+    let registry = registry_beside(path);
+    let (settings, rejected) = merge_fields(&doc, &registry);
+    for error in validate(&settings, &registry) {
+        eprintln!("Invalid settings field {}: {}", error.field, error.message);
+    }
+    if !rejected.is_empty() || from_version < SCHEMA_VERSION {
+        rewrite(path, &settings, &rejected, &registry);
+    }
+    settings
+}
+
+/// Replace the file at `path` with cleaned-up `settings`, backing up the
+/// original first. Settings that still don't validate, such as kept invalid
+/// project filters, would fail to save on every start, so the file is left
+/// alone until the user fixes it rather than collecting a backup per run.
+fn rewrite(path: &Path, settings: &AppSettings, rejected: &[String], registry: &ModelRegistry) {
+    if !validate(settings, registry).is_empty() {
+        if !rejected.is_empty() {
+            eprintln!(
+                "Ignoring invalid settings fields [{}]; the file is left as is",
+                rejected.join(", ")
+            );
+        }
+        return;
+    }
+    let backup_path = backup(path);
+    match save_settings_to(path, settings) {
+        Ok(()) => {
+            if let (Some(b), false) = (&backup_path, rejected.is_empty()) {
+                eprintln!(
+                    "Reset invalid settings fields [{}], original backed up to {}",
+                    rejected.join(", "),
+                    b.display()
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to rewrite settings: {}", e);
+            // The original is still in place
+            if let Some(b) = backup_path {
+                let _ = fs::remove_file(b);
+            }
+        }
+    }
+}
+
 pub fn load_settings() -> AppSettings {
-    let path = settings_path();
-    if path.exists() {
-        let content = fs::read_to_string(&path).unwrap_or_default();
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        AppSettings::default()
+    load_settings_from(&settings_path())
+}
+
+/// Strictly read settings from `path`: migrate in memory and validate, but
+/// never fall back, back up or rewrite. Used for hot-reloading external edits,
+/// where a half-typed file must not clobber what the user is editing.
+pub fn read_settings(path: &Path) -> Result<AppSettings, SettingsError> {
+    let content = fs::read_to_string(path)?;
+    let mut doc: Map<String, Value> = serde_json::from_str(&content)?;
+    migrate(&mut doc);
+    let settings: AppSettings = serde_json::from_value(Value::Object(doc))?;
+    let errors = validate(&settings, &registry_beside(path));
+    if !errors.is_empty() {
+        return Err(SettingsError::Invalid(errors));
+    }
+    Ok(settings)
+}
+
+/// Write `content` to a sibling temp file, flush it to disk, then rename it
+/// over `path` so readers never observe a truncated file.
+pub fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
     }
+    result
 }
 
-pub fn save_settings(settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
-    let path = settings_path();
-    let content = serde_json::to_string_pretty(settings)?;
-    fs::write(path, content)?;
+pub fn save_settings_to(path: &Path, settings: &AppSettings) -> Result<(), SettingsError> {
+    let errors = validate(settings, &registry_beside(path));
+    if !errors.is_empty() {
+        return Err(SettingsError::Invalid(errors));
+    }
+    let mut settings = settings.clone();
+    settings.schema_version = SCHEMA_VERSION;
+    let content = serde_json::to_string_pretty(&settings)?;
+    write_atomic(path, &content)?;
     Ok(())
 }
+
+pub fn save_settings(settings: &AppSettings) -> Result<(), SettingsError> {
+    save_settings_to(&settings_path(), settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_settings(dir: &TempDir, content: &str) -> PathBuf {
+        let path = dir.path().join("claudepulse-settings.json");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn backups(dir: &TempDir) -> usize {
+        fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains(".bak-"))
+            .count()
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        let settings = load_settings_from(&dir.path().join("nope.json"));
+        assert_eq!(settings.refresh_interval_secs, 180);
+        assert_eq!(backups(&dir), 0);
+    }
+
+    #[test]
+    fn test_migrates_unversioned_file() {
+        let dir = TempDir::new().unwrap();
+        let path = write_settings(
+            &dir,
+            r#"{"refresh_interval_secs":60,"window_hours":5.0,"usage_limit_tokens":null,"theme":"dark"}"#,
+        );
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert_eq!(settings.refresh_interval_secs, 60);
+        assert_eq!(settings.theme, "dark");
+
+        let on_disk: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(on_disk["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_missing_and_invalid_fields_fall_back_individually() {
+        let dir = TempDir::new().unwrap();
+        let path = write_settings(
+            &dir,
+            r#"{"schema_version":1,"refresh_interval_secs":0,"window_hours":"five","theme":"dark"}"#,
+        );
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.refresh_interval_secs, 180);
+        assert_eq!(settings.window_hours, 5.0);
+        assert_eq!(settings.theme, "dark");
+        assert_eq!(backups(&dir), 1);
+    }
+
+    #[test]
+    fn test_unreadable_file_is_backed_up() {
+        let dir = TempDir::new().unwrap();
+        let path = write_settings(&dir, "{not json");
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.theme, "system");
+        assert_eq!(backups(&dir), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{not json");
+    }
+
+    #[test]
+    fn test_validate_rejects_nonsense() {
+        let settings = AppSettings {
+            refresh_interval_secs: 0,
+            window_hours: -1.0,
+            usage_limit_tokens: Some(0),
+            theme: "neon".into(),
+            idle_threshold_mins: 0,
+            team_dir: Some(" ".into()),
+            exclude_projects: vec!["/work/[client".into()],
+            display_currency: Some("EUR".into()),
+            exchange_rates_as_of: Some("yesterday".into()),
+            budgets: vec![crate::models::Budget {
+                period: crate::models::BudgetPeriod::Daily,
+                amount_usd: 0.0,
+                project: None,
+                model: None,
+            }],
+            project_aliases: vec![crate::models::ProjectAlias {
+                alias: "acme".into(),
+                tags: vec!["".into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let fields: Vec<&str> = validate(&settings, &ModelRegistry::builtin())
+            .iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "refresh_interval_secs",
+                "window_hours",
+                "usage_limit_tokens",
+                "theme",
+                "idle_threshold_mins",
+                "team_dir",
+                "exclude_projects",
+                "budgets",
+                "display_currency",
+                "exchange_rates_as_of",
+                "project_aliases"
+            ]
+        );
+        assert!(validate(&AppSettings::default(), &ModelRegistry::builtin()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_families() {
+        let cap = |family: &str| crate::models::ModelCap {
+            family: family.into(),
+            window_tokens: None,
+            weekly_tokens: Some(1_000),
+        };
+        let mut settings = AppSettings {
+            weekly_family_caps: [("Opus".to_string(), 1_000)].into(),
+            ..Default::default()
+        };
+        settings.custom_limits.model_caps = vec![cap("sonnet")];
+        assert!(validate(&settings, &ModelRegistry::builtin()).is_empty());
+
+        settings.weekly_family_caps = [("opus-4".to_string(), 1_000)].into();
+        settings.custom_limits.model_caps = vec![cap("gpt")];
+        let fields: Vec<&str> = validate(&settings, &ModelRegistry::builtin())
+            .iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["custom_limits", "weekly_family_caps"]);
+
+        // Families from the registry file next to the settings file count
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(REGISTRY_FILE_NAME),
+            r#"{"models":[{"family":"mythos","version":"1","pricing":{"input":1,"output":2,"cache_read":0.1,"cache_create":1.25}}]}"#,
+        )
+        .unwrap();
+        let path = write_settings(
+            &dir,
+            r#"{"schema_version":1,"weekly_family_caps":{"mythos":1000}}"#,
+        );
+        assert_eq!(load_settings_from(&path).weekly_family_caps["mythos"], 1000);
+    }
+
+    #[test]
+    fn test_save_is_atomic_and_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("claudepulse-settings.json");
+        let settings = AppSettings {
+            refresh_interval_secs: 60,
+            theme: "dark".into(),
+            ..Default::default()
+        };
+
+        save_settings_to(&path, &settings).unwrap();
+        assert_eq!(read_settings(&path).unwrap(), settings);
+
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_cross_field_settings_survive_reload() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("claudepulse-settings.json");
+        let settings = AppSettings {
+            display_currency: Some("EUR".into()),
+            exchange_rates: [("EUR".to_string(), 0.9)].into(),
+            ..Default::default()
+        };
+
+        // `display_currency` sorts before the `exchange_rates` it needs
+        save_settings_to(&path, &settings).unwrap();
+        assert_eq!(load_settings_from(&path), settings);
+        assert_eq!(backups(&dir), 0);
+    }
+
+    #[test]
+    fn test_invalid_project_filters_are_kept() {
+        let dir = TempDir::new().unwrap();
+        let path = write_settings(
+            &dir,
+            r#"{"schema_version":1,"exclude_projects":["/work/[client"],"theme":"neon"}"#,
+        );
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.exclude_projects, vec!["/work/[client"]);
+        assert_eq!(settings.theme, "system");
+        // The filter can't be saved as is, so the file stays untouched and
+        // loading again doesn't pile up backups
+        assert!(fs::read_to_string(&path).unwrap().contains("neon"));
+        load_settings_from(&path);
+        assert_eq!(backups(&dir), 0);
+    }
+
+    #[test]
+    fn test_read_settings_is_strict() {
+        let dir = TempDir::new().unwrap();
+        let path = write_settings(&dir, r#"{"schema_version":1,"window_hours":0}"#);
+        assert!(matches!(
+            read_settings(&path),
+            Err(SettingsError::Invalid(_))
+        ));
+
+        let path = write_settings(&dir, r#"{"window_hours":"#);
+        assert!(matches!(read_settings(&path), Err(SettingsError::Json(_))));
+        // Strict reads never touch the file
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"window_hours":"#);
+        assert_eq!(backups(&dir), 0);
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_settings() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("claudepulse-settings.json");
+        let settings = AppSettings {
+            window_hours: 0.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            save_settings_to(&path, &settings),
+            Err(SettingsError::Invalid(_))
+        ));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::TimeZone;
+
+use crate::context::SnapshotContext;
+use crate::imports::{read_export, UsageExport};
+use crate::models::{MemberUsage, TeamModelUsage, TeamProjectUsage, TeamSnapshot};
+use crate::parser::{merge_sorted, ParsedUsage};
+use crate::privacy::Privacy;
+
+#[derive(Default)]
+struct Totals {
+    message_count: u64,
+    tokens: u64,
+    cost_usd: f64,
+}
+
+/// Export files in `team_dir`: `<user>.json` at the top level, or one file
+/// per machine in `<user>/`. Each is returned with the user it belongs to,
+/// which is the name recorded in the export when there is one. Member
+/// directories that can't be listed are added to `unreadable`.
+fn find_exports(
+    team_dir: &Path,
+    unreadable: &mut Vec<String>,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    let list = |dir: &Path| -> io::Result<Vec<PathBuf>> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+        Ok(paths)
+    };
+    let is_export = |path: &Path| path.is_file() && path.extension().is_some_and(|e| e == "json");
+    let name = |name: Option<&OsStr>| name.unwrap_or_default().to_string_lossy().to_string();
+
+    let mut exports = Vec::new();
+    let top = list(team_dir).map_err(|e| format!("{}: {}", team_dir.display(), e))?;
+    for path in top {
+        if path.is_dir() {
+            match list(&path) {
+                Ok(files) => {
+                    let user = name(path.file_name());
+                    exports.extend(
+                        files
+                            .into_iter()
+                            .filter(|f| is_export(f))
+                            .map(|f| (f, user.clone())),
+                    );
+                }
+                Err(_) => unreadable.push(path.display().to_string()),
+            }
+        } else if is_export(&path) {
+            let user = name(path.file_stem());
+            exports.push((path, user));
+        }
+    }
+    Ok(exports)
+}
+
+/// Combined usage this week, from local Monday to now, of everyone with
+/// exports in `team_dir`. A member's machines are merged and responses
+/// exported from more than one of them count once. Project filters and
+/// anonymization in `privacy` apply to everyone's usage. Fails when
+/// `team_dir` can't be listed.
+pub fn team_snapshot<Tz: TimeZone>(
+    ctx: &SnapshotContext<Tz>,
+    team_dir: &Path,
+    privacy: &Privacy,
+) -> Result<TeamSnapshot, String> {
+    let start = ctx.start_of_day(ctx.week_start_date());
+    let registry = ctx.registry();
+
+    let mut unreadable_files = Vec::new();
+    let exports = find_exports(team_dir, &mut unreadable_files)?;
+    // Members without usage this week are still listed
+    let mut by_user: HashMap<String, Totals> = HashMap::new();
+    let mut members: HashMap<String, (BTreeSet<String>, Vec<ParsedUsage>)> = HashMap::new();
+    for (path, user) in exports {
+        let export: UsageExport = match read_export(&path) {
+            Ok(e) => e,
+            Err(_) => {
+                unreadable_files.push(path.display().to_string());
+                continue;
+            }
+        };
+        let user = if export.user.is_empty() {
+            user
+        } else {
+            export.user
+        };
+        by_user.entry(user.clone()).or_default();
+        let member = members.entry(user).or_default();
+        member.0.insert(export.machine);
+        let mut parsed = ParsedUsage {
+            entries: export.entries,
+            ..Default::default()
+        };
+        privacy.filter(&mut parsed);
+        member.1.push(parsed);
+    }
+
+    let mut by_model: HashMap<String, Totals> = HashMap::new();
+    let mut by_project: HashMap<String, Totals> = HashMap::new();
+    let mut project_users: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut machines: HashMap<String, Vec<String>> = HashMap::new();
+    for (user, (user_machines, per_machine)) in members {
+        machines.insert(user.clone(), user_machines.into_iter().collect());
+        let mut merged = merge_sorted(per_machine);
+        privacy.display(&mut merged);
+        let entries = merged.entries;
+        for entry in entries
+            .iter()
+            .filter(|e| e.timestamp >= start && e.timestamp <= ctx.now)
+        {
+            let tokens = entry.usage.total();
+            let cost = registry.resolve(&entry.model).pricing.cost(&entry.usage);
+            for totals in [
+                by_user.entry(user.clone()).or_default(),
+                by_model.entry(entry.model.clone()).or_default(),
+                by_project.entry(entry.project.clone()).or_default(),
+            ] {
+                totals.message_count += 1;
+                totals.tokens += tokens;
+                totals.cost_usd += cost;
+            }
+            project_users
+                .entry(entry.project.clone())
+                .or_default()
+                .insert(user.clone());
+        }
+    }
+
+    let mut by_user: Vec<MemberUsage> = by_user
+        .into_iter()
+        .map(|(user, t)| MemberUsage {
+            machines: machines.remove(&user).unwrap_or_default(),
+            user,
+            message_count: t.message_count,
+            tokens: t.tokens,
+            cost_usd: t.cost_usd,
+        })
+        .collect();
+    by_user.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.user.cmp(&b.user))
+    });
+
+    let mut by_model: Vec<TeamModelUsage> = by_model
+        .into_iter()
+        .map(|(model, t)| TeamModelUsage {
+            display_name: registry.resolve(&model).display_name,
+            model,
+            message_count: t.message_count,
+            tokens: t.tokens,
+            cost_usd: t.cost_usd,
+        })
+        .collect();
+    by_model.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+
+    let mut by_project: Vec<TeamProjectUsage> = by_project
+        .into_iter()
+        .map(|(project, t)| TeamProjectUsage {
+            users: project_users
+                .remove(&project)
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            project,
+            message_count: t.message_count,
+            tokens: t.tokens,
+            cost_usd: t.cost_usd,
+        })
+        .collect();
+    by_project.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    unreadable_files.sort();
+
+    Ok(TeamSnapshot {
+        period_start: start.to_rfc3339(),
+        period_end: ctx.now.to_rfc3339(),
+        total_tokens: by_user.iter().map(|m| m.tokens).sum(),
+        total_cost_usd: by_user.iter().map(|m| m.cost_usd).sum(),
+        by_user,
+        by_model,
+        by_project,
+        unreadable_files,
+        last_updated: ctx.now.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_tz::CentralEurope;
+    use crate::models::TokenUsage;
+    use crate::parser::UsageEntry;
+    use chrono::{DateTime, Utc};
+    use std::fs;
+
+    fn entry(request: &str, time: &str, project: &str, output_tokens: u64) -> UsageEntry {
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens,
+                ..Default::default()
+            },
+            timestamp: time.parse().unwrap(),
+            project: project.into(),
+            request_id: request.into(),
+            ..Default::default()
+        }
+    }
+
+    fn write_export(path: &Path, machine: &str, user: &str, entries: Vec<UsageEntry>) {
+        let export = UsageExport {
+            version: crate::imports::EXPORT_VERSION,
+            machine: machine.into(),
+            user: user.into(),
+            exported_at: DateTime::<Utc>::default(),
+            entries,
+            prompts: vec![],
+        };
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, serde_json::to_string(&export).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_team_snapshot() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        // Glob metacharacters in the path are taken literally
+        let dir = tmp.path().join("Team [NL]");
+        let monday = "2026-02-09T09:00:00Z";
+        write_export(
+            &dir.join("alice.json"),
+            "mbp",
+            "",
+            vec![
+                entry("a1", monday, "/work/acme", 1_000_000),
+                // Last week
+                entry("a0", "2026-02-06T09:00:00Z", "/work/acme", 5_000_000),
+            ],
+        );
+        // Bob exports from two machines that both saw one session
+        let shared = entry("b1", monday, "/work/site", 200_000);
+        write_export(
+            &dir.join("bob").join("laptop.json"),
+            "laptop",
+            "",
+            vec![shared.clone(), entry("b2", monday, "/work/acme", 100_000)],
+        );
+        write_export(&dir.join("bob").join("desk.json"), "desk", "", vec![shared]);
+        fs::write(dir.join("carol.json"), "not json").unwrap();
+
+        let ctx = SnapshotContext {
+            now: "2026-02-10T12:00:00Z".parse().unwrap(),
+            tz: CentralEurope,
+            projects_dir: PathBuf::new(),
+            registry_file: None,
+            imports_dir: None,
+        };
+        let team = team_snapshot(&ctx, &dir, &Privacy::default()).unwrap();
+
+        let users: Vec<(&str, u64, usize)> = team
+            .by_user
+            .iter()
+            .map(|m| (m.user.as_str(), m.message_count, m.machines.len()))
+            .collect();
+        assert_eq!(users, vec![("alice", 1, 1), ("bob", 2, 2)]);
+        // 1.3 MTok output at $15
+        assert!((team.total_cost_usd - 19.5).abs() < 1e-9);
+        assert_eq!(team.by_model[0].message_count, 3);
+        assert_eq!(team.by_project[0].project, "/work/acme");
+        assert_eq!(team.by_project[0].users, vec!["alice", "bob"]);
+        assert_eq!(team.unreadable_files.len(), 1);
+
+        // The reader's aliases match the real paths in exports, and an
+        // aliased project is hashed once
+        let privacy = Privacy::from_settings(&crate::models::AppSettings {
+            project_aliases: vec![crate::models::ProjectAlias {
+                alias: "acme".into(),
+                paths: vec!["/work/acme".into()],
+                tags: vec![],
+            }],
+            anonymize: true,
+            ..Default::default()
+        });
+        let team = team_snapshot(&ctx, &dir, &privacy).unwrap();
+        assert_eq!(
+            team.by_project[0].project,
+            crate::privacy::anonymize("project", "acme")
+        );
+
+        let missing = team_snapshot(&ctx, &tmp.path().join("gone"), &privacy);
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{McpServerStats, ToolStats, ToolUsage};
+use crate::parser::UsageEntry;
+use crate::registry::ModelRegistry;
+
+#[derive(Default)]
+struct Totals {
+    invocations: u64,
+    turns: u64,
+    tokens: u64,
+    cost_usd: f64,
+}
+
+impl Totals {
+    fn add_turn(&mut self, invocations: u64, tokens: u64, cost_usd: f64) {
+        self.invocations += invocations;
+        self.turns += 1;
+        self.tokens += tokens;
+        self.cost_usd += cost_usd;
+    }
+
+    fn into_stats(self, name: String) -> ToolStats {
+        ToolStats {
+            name,
+            invocations: self.invocations,
+            turns: self.turns,
+            tokens: self.tokens,
+            cost_usd: self.cost_usd,
+        }
+    }
+}
+
+/// Server name of an MCP tool, named `mcp__<server>__<tool>`
+fn mcp_server(tool: &str) -> Option<&str> {
+    let rest = tool.strip_prefix("mcp__")?;
+    rest.split_once("__").map(|(server, _)| server)
+}
+
+fn into_sorted(groups: HashMap<String, Totals>) -> Vec<ToolStats> {
+    let mut stats: Vec<ToolStats> = groups
+        .into_iter()
+        .map(|(name, totals)| totals.into_stats(name))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.invocations
+            .cmp(&a.invocations)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    stats
+}
+
+/// Invocation counts per tool and per MCP server for entries in
+/// `[start, end]`, with the tokens and cost of the turns that called them
+pub fn tool_usage(
+    registry: &ModelRegistry,
+    entries: &[UsageEntry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ToolUsage {
+    let mut by_tool: HashMap<String, Totals> = HashMap::new();
+    let mut by_server: HashMap<String, Totals> = HashMap::new();
+
+    for entry in entries
+        .iter()
+        .filter(|e| e.timestamp >= start && e.timestamp <= end && !e.tools.is_empty())
+    {
+        let tokens = entry.usage.total();
+        let cost = registry.resolve(&entry.model).pricing.cost(&entry.usage);
+
+        let mut calls: HashMap<&str, u64> = HashMap::new();
+        for tool in &entry.tools {
+            *calls.entry(tool).or_default() += 1;
+        }
+        let mut server_calls: HashMap<&str, u64> = HashMap::new();
+        for (tool, count) in &calls {
+            by_tool
+                .entry(tool.to_string())
+                .or_default()
+                .add_turn(*count, tokens, cost);
+            if let Some(server) = mcp_server(tool) {
+                *server_calls.entry(server).or_default() += count;
+            }
+        }
+        for (server, count) in server_calls {
+            by_server
+                .entry(server.to_string())
+                .or_default()
+                .add_turn(count, tokens, cost);
+        }
+    }
+
+    let tools = into_sorted(by_tool);
+    let mut mcp_servers: Vec<McpServerStats> = by_server
+        .into_iter()
+        .map(|(server, totals)| McpServerStats {
+            tools: tools
+                .iter()
+                .filter(|t| mcp_server(&t.name) == Some(server.as_str()))
+                .cloned()
+                .collect(),
+            server,
+            invocations: totals.invocations,
+            turns: totals.turns,
+            tokens: totals.tokens,
+            cost_usd: totals.cost_usd,
+        })
+        .collect();
+    mcp_servers.sort_by(|a, b| {
+        b.invocations
+            .cmp(&a.invocations)
+            .then_with(|| a.server.cmp(&b.server))
+    });
+
+    ToolUsage { tools, mcp_servers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenUsage;
+    use chrono::Duration;
+    use std::collections::BTreeSet;
+
+    fn entry(minutes: i64, tools: &[&str]) -> UsageEntry {
+        let t0: DateTime<Utc> = "2026-02-06T10:00:00Z".parse().unwrap();
+        UsageEntry {
+            model: "claude-sonnet-4-5-20250929".into(),
+            usage: TokenUsage {
+                output_tokens: 1_000_000,
+                ..Default::default()
+            },
+            timestamp: t0 + Duration::minutes(minutes),
+            tools: tools.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tool_usage() {
+        let entries = vec![
+            entry(0, &["Bash", "Bash", "Read"]),
+            entry(
+                1,
+                &["Bash", "mcp__github__get_issue", "mcp__github__list_prs"],
+            ),
+            entry(2, &[]),
+        ];
+        let start = entries[0].timestamp;
+        let end = start + Duration::hours(1);
+
+        let usage = tool_usage(&ModelRegistry::builtin(), &entries, start, end);
+
+        let bash = &usage.tools[0];
+        assert_eq!(bash.name, "Bash");
+        assert_eq!(bash.invocations, 3);
+        assert_eq!(bash.turns, 2);
+        assert_eq!(bash.tokens, 2_000_000);
+        // 2 MTok output at $15
+        assert!((bash.cost_usd - 30.0).abs() < 1e-9);
+
+        assert_eq!(usage.mcp_servers.len(), 1);
+        let github = &usage.mcp_servers[0];
+        assert_eq!(github.server, "github");
+        assert_eq!(github.invocations, 2);
+        // Both calls were in one turn
+        assert_eq!(github.turns, 1);
+        assert_eq!(github.tokens, 1_000_000);
+        let names: BTreeSet<&str> = github.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["mcp__github__get_issue", "mcp__github__list_prs"].into()
+        );
+    }
+}
@@ -2,15 +2,15 @@ use notify::RecursiveMode;
 use notify_debouncer_mini::new_debouncer;
 use std::sync::mpsc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::archive::{is_archive, is_transcript};
+use crate::commands::AppState;
+use crate::context::SnapshotContext;
 
 pub fn start_watcher(app_handle: AppHandle) {
     std::thread::spawn(move || {
-        let home = match dirs::home_dir() {
-            Some(h) => h,
-            None => return,
-        };
-        let watch_path = home.join(".claude").join("projects");
+        let watch_path = SnapshotContext::system().projects_dir;
 
         if !watch_path.exists() {
             return;
@@ -30,20 +30,70 @@ pub fn start_watcher(app_handle: AppHandle) {
             return;
         }
 
-        loop {
-            match rx.recv() {
-                Ok(Ok(events)) => {
-                    let has_jsonl = events.iter().any(|e| {
-                        e.path
-                            .extension()
-                            .map(|ext| ext == "jsonl")
-                            .unwrap_or(false)
-                    });
-                    if has_jsonl {
+        while let Ok(Ok(events)) = rx.recv() {
+            // Compressed transcripts and archives are read as well
+            let has_usage = events
+                .iter()
+                .any(|e| is_transcript(&e.path) || is_archive(&e.path));
+            if has_usage {
+                let _ = app_handle.emit("usage-data-changed", ());
+            }
+        }
+    });
+}
+
+/// Watch `claudepulse-settings.json` for edits made outside the app. Valid
+/// changes replace `AppState.settings` and are broadcast as `settings-changed`;
+/// invalid ones are reported as `settings-invalid` and otherwise ignored.
+pub fn start_settings_watcher(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let settings_path = crate::settings::settings_path();
+        // Watch the directory rather than the file: atomic saves replace the
+        // file via rename, which would orphan a watch on the old inode.
+        let watch_dir = match settings_path.parent() {
+            Some(dir) if dir.exists() => dir.to_path_buf(),
+            _ => return,
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let mut debouncer = match new_debouncer(Duration::from_millis(500), tx) {
+            Ok(d) => d,
+            Err(_) => return,
+        };
+
+        if debouncer
+            .watcher()
+            .watch(&watch_dir, RecursiveMode::NonRecursive)
+            .is_err()
+        {
+            return;
+        }
+
+        while let Ok(Ok(events)) = rx.recv() {
+            let touched = events
+                .iter()
+                .any(|e| e.path.file_name() == settings_path.file_name());
+            if !touched || !settings_path.exists() {
+                continue;
+            }
+            match crate::settings::read_settings(&settings_path) {
+                Ok(new_settings) => {
+                    let state = app_handle.state::<AppState>();
+                    let Ok(mut settings) = state.settings.lock() else {
+                        break;
+                    };
+                    // Our own saves land here too; only broadcast real changes
+                    if *settings != new_settings {
+                        *settings = new_settings.clone();
+                        drop(settings);
+                        let _ = app_handle.emit("settings-changed", new_settings);
+                        // Window length may have changed
                         let _ = app_handle.emit("usage-data-changed", ());
                     }
                 }
-                Ok(Err(_)) | Err(_) => break,
+                Err(e) => {
+                    let _ = app_handle.emit("settings-invalid", e.to_string());
+                }
             }
         }
     });
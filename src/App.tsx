@@ -39,7 +39,7 @@ export default function App() {
         <div className="content">
           <WindowCard
             usage={data.window}
-            limit={settings.usage_limit_tokens}
+            limit={data.plan_usage.window.limit_tokens}
           />
           <WeeklyCard usage={data.weekly} />
           <ModelBreakdown models={data.models} />
@@ -6,12 +6,8 @@ interface ModelBreakdownProps {
   models: ModelUsage[];
 }
 
-function modelKey(displayName: string): string {
-  const lower = displayName.toLowerCase();
-  if (lower.includes('opus')) return 'opus';
-  if (lower.includes('sonnet')) return 'sonnet';
-  if (lower.includes('haiku')) return 'haiku';
-  return 'sonnet';
+function modelKey(family: string): string {
+  return ['opus', 'sonnet', 'haiku'].includes(family) ? family : 'sonnet';
 }
 
 export function ModelBreakdown({ models }: ModelBreakdownProps) {
@@ -31,7 +27,7 @@ export function ModelBreakdown({ models }: ModelBreakdownProps) {
       <span className={styles.label}>Model Breakdown</span>
       <div className={styles.list}>
         {models.map((model) => {
-          const key = modelKey(model.display_name);
+          const key = modelKey(model.family);
           return (
             <div key={model.model} className={styles.row}>
               <div className={styles.modelInfo}>
@@ -80,6 +80,26 @@ export function SettingsPanel({
           </span>
         </div>
 
+        <div className={styles.field}>
+          <label className={styles.fieldLabel}>Plan</label>
+          <select
+            className={styles.select}
+            value={local.plan}
+            onChange={(e) =>
+              handleChange({ plan: e.target.value as AppSettings['plan'] })
+            }
+          >
+            <option value="pro">Pro</option>
+            <option value="max5x">Max 5x</option>
+            <option value="max20x">Max 20x</option>
+            <option value="api">API (pay-as-you-go)</option>
+            <option value="custom">Custom</option>
+          </select>
+          <span className={styles.fieldHint}>
+            Presets are estimates; the token limit below overrides the window
+          </span>
+        </div>
+
         <div className={styles.field}>
           <label className={styles.fieldLabel}>
             Token Limit (for usage meter)
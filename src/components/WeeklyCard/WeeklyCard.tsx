@@ -13,6 +13,9 @@ export function WeeklyCard({ usage }: WeeklyCardProps) {
     ...usage.daily_breakdown.map((d) => d.output_tokens),
     1,
   );
+  const cappedFamilies = usage.by_family.filter(
+    (f) => f.cap.utilization != null,
+  );
 
   return (
     <div className={styles.card}>
@@ -66,6 +69,17 @@ export function WeeklyCard({ usage }: WeeklyCardProps) {
           </span>
         </div>
       </div>
+
+      {cappedFamilies.length > 0 && (
+        <div className={styles.caps}>
+          {cappedFamilies.map((f) => (
+            <span key={f.family} className={styles.capItem}>
+              {f.family[0].toUpperCase() + f.family.slice(1)}:{' '}
+              {((f.cap.utilization ?? 0) * 100).toFixed(0)}% of weekly
+            </span>
+          ))}
+        </div>
+      )}
     </div>
   );
 }
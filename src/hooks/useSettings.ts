@@ -1,12 +1,28 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type { AppSettings } from '../types';
 
 const DEFAULT_SETTINGS: AppSettings = {
+  schema_version: 1,
   refresh_interval_secs: 180,
   window_hours: 5.0,
   usage_limit_tokens: null,
   theme: 'system',
+  plan: 'custom',
+  custom_limits: { window_tokens: null, weekly_tokens: null, model_caps: [] },
+  weekly_family_caps: {},
+  heatmap_days: null,
+  idle_threshold_mins: 15,
+  team_dir: null,
+  include_projects: [],
+  exclude_projects: [],
+  anonymize: false,
+  project_aliases: [],
+  budgets: [],
+  display_currency: null,
+  exchange_rates: {},
+  exchange_rates_as_of: null,
 };
 
 export function useSettings() {
@@ -16,6 +32,14 @@ export function useSettings() {
     invoke<AppSettings>('get_settings')
       .then(setSettings)
       .catch(() => {});
+
+    const unlisten = listen<AppSettings>('settings-changed', (event) => {
+      setSettings(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   const updateSettings = useCallback(async (newSettings: AppSettings) => {
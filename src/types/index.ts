@@ -1,6 +1,7 @@
 export interface ModelUsage {
   model: string;
   display_name: string;
+  family: string;
   input_tokens: number;
   output_tokens: number;
   cache_read_tokens: number;
@@ -8,6 +9,15 @@ export interface ModelUsage {
   message_count: number;
 }
 
+export interface PromptStats {
+  prompt_count: number;
+  response_count: number;
+  responses_per_prompt: number | null;
+  tokens_per_prompt: number | null;
+  cost_per_prompt: number | null;
+  cost_per_prompt_display?: number | null;
+}
+
 export interface WindowUsage {
   total_input_tokens: number;
   total_output_tokens: number;
@@ -15,6 +25,7 @@ export interface WindowUsage {
   total_cache_creation_tokens: number;
   message_count: number;
   session_count: number;
+  prompts: PromptStats;
   window_start: string;
   window_end: string;
 }
@@ -26,6 +37,47 @@ export interface DailyUsage {
   message_count: number;
 }
 
+export interface FamilyUsage {
+  family: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_creation_tokens: number;
+  message_count: number;
+  cap: LimitUsage;
+}
+
+export interface MetricDelta {
+  current: number;
+  previous: number;
+  change: number;
+  percent_change: number | null;
+}
+
+export interface ModelComparison {
+  model: string;
+  display_name: string;
+  tokens: MetricDelta;
+  messages: MetricDelta;
+  sessions: MetricDelta;
+  cost_usd: MetricDelta;
+  cost_display?: MetricDelta;
+}
+
+export interface PeriodComparison {
+  current_start: string;
+  current_end: string;
+  previous_start: string;
+  previous_end: string;
+  tokens: MetricDelta;
+  messages: MetricDelta;
+  sessions: MetricDelta;
+  cost_usd: MetricDelta;
+  cost_display?: MetricDelta;
+  by_model: ModelComparison[];
+  display_currency?: DisplayCurrency;
+}
+
 export interface WeeklyUsage {
   total_input_tokens: number;
   total_output_tokens: number;
@@ -33,34 +85,358 @@ export interface WeeklyUsage {
   total_cache_creation_tokens: number;
   message_count: number;
   session_count: number;
+  prompts: PromptStats;
+  active_secs: number;
+  messages_per_active_hour: number | null;
+  cost_per_active_hour: number | null;
+  cost_per_active_hour_display?: number | null;
   daily_breakdown: DailyUsage[];
+  by_family: FamilyUsage[];
+  vs_previous_week: PeriodComparison;
 }
 
 export interface ModelCost {
   model: string;
   display_name: string;
   cost_usd: number;
+  cost_display?: number;
 }
 
 export interface CostEstimate {
   window_cost_usd: number;
+  window_cost_display?: number;
   weekly_cost_usd: number;
+  weekly_cost_display?: number;
   by_model: ModelCost[];
 }
 
+export interface LimitUsage {
+  used_tokens: number;
+  limit_tokens: number | null;
+  utilization: number | null;
+}
+
+export interface ModelCapUsage {
+  family: string;
+  window: LimitUsage;
+  weekly: LimitUsage;
+}
+
+export interface PlanUsage {
+  plan: Plan;
+  window: LimitUsage;
+  weekly: LimitUsage;
+  model_caps: ModelCapUsage[];
+}
+
+export interface CacheStats {
+  key: string;
+  input_tokens: number;
+  cache_read_tokens: number;
+  cache_creation_tokens: number;
+  hit_ratio: number;
+  saved_usd: number;
+  saved_display?: number;
+  cache_write_usd: number;
+  cache_write_display?: number;
+  wasted_write_tokens: number;
+  wasted_write_usd: number;
+  wasted_write_display?: number;
+}
+
+export interface CacheAnalytics {
+  by_model: CacheStats[];
+  by_session: CacheStats[];
+  by_project: CacheStats[];
+}
+
+export interface BranchUsage {
+  project: string;
+  branch: string;
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface VersionUsage {
+  version: string;
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+  tokens_per_message: number;
+}
+
+export interface MachineUsage {
+  machine: string;
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface ProjectUsage {
+  project: string;
+  tags: string[];
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface TagUsage {
+  tag: string;
+  projects: string[];
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface UsageAttribution {
+  by_branch: BranchUsage[];
+  by_version: VersionUsage[];
+  by_machine: MachineUsage[];
+  by_project: ProjectUsage[];
+  by_tag: TagUsage[];
+}
+
+export interface SessionStats {
+  session_id: string;
+  project: string;
+  start: string;
+  end: string;
+  duration_secs: number;
+  active_secs: number;
+  prompt_count: number;
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+  messages_per_active_hour: number | null;
+  cost_per_active_hour: number | null;
+  cost_per_active_hour_display?: number | null;
+}
+
+export interface ToolStats {
+  name: string;
+  invocations: number;
+  turns: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface McpServerStats {
+  server: string;
+  invocations: number;
+  turns: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+  tools: ToolStats[];
+}
+
+export interface ToolUsage {
+  tools: ToolStats[];
+  mcp_servers: McpServerStats[];
+}
+
+export interface HeatmapCell {
+  tokens: number;
+  messages: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface UsageHeatmap {
+  range_start: string;
+  range_end: string;
+  /** cells[weekday][hour], Monday = 0 */
+  cells: HeatmapCell[][];
+  display_currency?: DisplayCurrency;
+}
+
+export interface LimitHitInfo {
+  timestamp: string;
+  session_id: string;
+  window_tokens: number;
+  resets_at: string | null;
+}
+
+export interface LimitHistory {
+  hits: LimitHitInfo[];
+  suggested_usage_limit_tokens: number | null;
+}
+
+export type SkipReason =
+  | 'io'
+  | 'invalid_json'
+  | 'unknown_type'
+  | 'missing_message'
+  | 'invalid_timestamp'
+  | 'missing_model'
+  | 'missing_usage'
+  | 'invalid_usage';
+
+export interface SkipLocation {
+  file: string;
+  line: number;
+}
+
+export interface SkipCount {
+  reason: SkipReason;
+  count: number;
+  samples: SkipLocation[];
+}
+
+export interface ParseDiagnostics {
+  files_scanned: number;
+  lines_read: number;
+  entries_parsed: number;
+  duplicate_lines: number;
+  cross_file_duplicates: number;
+  skipped: SkipCount[];
+}
+
+export interface ImportSummary {
+  machine: string;
+  imported: number;
+  duplicates: number;
+  total: number;
+}
+
+export interface MemberUsage {
+  user: string;
+  machines: string[];
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface TeamModelUsage {
+  model: string;
+  display_name: string;
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface TeamProjectUsage {
+  project: string;
+  users: string[];
+  message_count: number;
+  tokens: number;
+  cost_usd: number;
+  cost_display?: number;
+}
+
+export interface TeamSnapshot {
+  period_start: string;
+  period_end: string;
+  total_tokens: number;
+  total_cost_usd: number;
+  total_cost_display?: number;
+  by_user: MemberUsage[];
+  by_model: TeamModelUsage[];
+  by_project: TeamProjectUsage[];
+  unreadable_files: string[];
+  last_updated: string;
+  display_currency?: DisplayCurrency;
+}
+
+export interface BudgetStatus {
+  budget: Budget;
+  period_start: string;
+  period_end: string;
+  spent_usd: number;
+  spent_display?: number;
+  remaining_usd: number;
+  remaining_display?: number;
+  utilization: number;
+  projected_usd: number;
+  projected_display?: number;
+  projected_overrun_usd: number | null;
+  projected_overrun_display?: number | null;
+}
+
 export interface UsageSnapshot {
   window: WindowUsage;
   weekly: WeeklyUsage;
   models: ModelUsage[];
   cost_estimate: CostEstimate;
+  plan_usage: PlanUsage;
+  limit_history: LimitHistory;
+  cache_analytics: CacheAnalytics;
+  tool_usage: ToolUsage;
+  attribution: UsageAttribution;
+  sessions: SessionStats[];
+  heatmap: UsageHeatmap | null;
+  budgets: BudgetStatus[];
+  unrecognized_models: string[];
   last_updated: string;
+  display_currency?: DisplayCurrency;
+}
+
+export type Plan = 'pro' | 'max5x' | 'max20x' | 'api' | 'custom';
+
+export interface ModelCap {
+  family: string;
+  window_tokens: number | null;
+  weekly_tokens: number | null;
+}
+
+export interface PlanLimits {
+  window_tokens: number | null;
+  weekly_tokens: number | null;
+  model_caps: ModelCap[];
+}
+
+export interface DisplayCurrency {
+  code: string;
+  rate: number;
+  as_of: string | null;
+}
+
+export type BudgetPeriod = 'daily' | 'weekly' | 'monthly';
+
+export interface Budget {
+  period: BudgetPeriod;
+  amount_usd: number;
+  amount_display?: number;
+  project: string | null;
+  model: string | null;
+}
+
+export interface ProjectAlias {
+  alias: string;
+  paths: string[];
+  tags: string[];
 }
 
 export interface AppSettings {
+  schema_version: number;
   refresh_interval_secs: number;
   window_hours: number;
   usage_limit_tokens: number | null;
   theme: 'light' | 'dark' | 'system';
+  plan: Plan;
+  custom_limits: PlanLimits;
+  weekly_family_caps: Record<string, number>;
+  heatmap_days: number | null;
+  idle_threshold_mins: number;
+  team_dir: string | null;
+  include_projects: string[];
+  exclude_projects: string[];
+  anonymize: boolean;
+  project_aliases: ProjectAlias[];
+  budgets: Budget[];
+  display_currency: string | null;
+  exchange_rates: Record<string, number>;
+  exchange_rates_as_of: string | null;
 }
 
 export type Theme = 'light' | 'dark';
@@ -4,6 +4,8 @@ import { invoke } from '@tauri-apps/api/core';
 import App from '../../src/App';
 import type { UsageSnapshot, AppSettings } from '../../src/types';
 
+const flat = { current: 0, previous: 0, change: 0, percent_change: null };
+
 const mockSnapshot: UsageSnapshot = {
   window: {
     total_input_tokens: 5_000,
@@ -12,6 +14,13 @@ const mockSnapshot: UsageSnapshot = {
     total_cache_creation_tokens: 50_000,
     message_count: 42,
     session_count: 3,
+    prompts: {
+      prompt_count: 6,
+      response_count: 42,
+      responses_per_prompt: 7,
+      tokens_per_prompt: 367_500,
+      cost_per_prompt: 0.39,
+    },
     window_start: '2026-02-06T10:00:00Z',
     window_end: '2026-02-06T15:00:00Z',
   },
@@ -22,17 +31,40 @@ const mockSnapshot: UsageSnapshot = {
     total_cache_creation_tokens: 200_000,
     message_count: 250,
     session_count: 12,
+    prompts: {
+      prompt_count: 50,
+      response_count: 250,
+      responses_per_prompt: 5,
+      tokens_per_prompt: 220_400,
+      cost_per_prompt: 0.25,
+    },
+    active_secs: 36_000,
+    messages_per_active_hour: 25,
+    cost_per_active_hour: 1.25,
     daily_breakdown: [
       { date: '2026-02-03', input_tokens: 3000, output_tokens: 120_000, message_count: 40 },
       { date: '2026-02-04', input_tokens: 5000, output_tokens: 200_000, message_count: 60 },
       { date: '2026-02-05', input_tokens: 4000, output_tokens: 180_000, message_count: 50 },
       { date: '2026-02-06', input_tokens: 8000, output_tokens: 300_000, message_count: 100 },
     ],
+    by_family: [],
+    vs_previous_week: {
+      current_start: '2026-02-02T00:00:00Z',
+      current_end: '2026-02-06T15:00:00Z',
+      previous_start: '2026-01-26T00:00:00Z',
+      previous_end: '2026-01-30T15:00:00Z',
+      tokens: flat,
+      messages: flat,
+      sessions: flat,
+      cost_usd: flat,
+      by_model: [],
+    },
   },
   models: [
     {
       model: 'claude-sonnet-4-5-20250929',
       display_name: 'Sonnet 4.5',
+      family: 'sonnet',
       input_tokens: 4_000,
       output_tokens: 120_000,
       cache_read_tokens: 1_500_000,
@@ -42,6 +74,7 @@ const mockSnapshot: UsageSnapshot = {
     {
       model: 'claude-opus-4-5-20251101',
       display_name: 'Opus 4.5',
+      family: 'opus',
       input_tokens: 1_000,
       output_tokens: 30_000,
       cache_read_tokens: 500_000,
@@ -57,14 +90,43 @@ const mockSnapshot: UsageSnapshot = {
       { model: 'claude-opus-4-5-20251101', display_name: 'Opus 4.5', cost_usd: 4.30 },
     ],
   },
+  plan_usage: {
+    plan: 'custom',
+    window: { used_tokens: 2_205_000, limit_tokens: null, utilization: null },
+    weekly: { used_tokens: 11_020_000, limit_tokens: null, utilization: null },
+    model_caps: [],
+  },
+  limit_history: { hits: [], suggested_usage_limit_tokens: null },
+  cache_analytics: { by_model: [], by_session: [], by_project: [] },
+  tool_usage: { tools: [], mcp_servers: [] },
+  attribution: { by_branch: [], by_version: [], by_machine: [], by_project: [], by_tag: [] },
+  sessions: [],
+  heatmap: null,
+  budgets: [],
+  unrecognized_models: [],
   last_updated: new Date().toISOString(),
 };
 
 const mockSettings: AppSettings = {
+  schema_version: 1,
   refresh_interval_secs: 180,
   window_hours: 5.0,
   usage_limit_tokens: null,
   theme: 'light',
+  plan: 'custom',
+  custom_limits: { window_tokens: null, weekly_tokens: null, model_caps: [] },
+  weekly_family_caps: {},
+  heatmap_days: null,
+  idle_threshold_mins: 15,
+  team_dir: null,
+  include_projects: [],
+  exclude_projects: [],
+  anonymize: false,
+  project_aliases: [],
+  budgets: [],
+  display_currency: null,
+  exchange_rates: {},
+  exchange_rates_as_of: null,
 };
 
 describe('App', () => {
@@ -7,6 +7,7 @@ const mockModels: ModelUsage[] = [
   {
     model: 'claude-sonnet-4-5-20250929',
     display_name: 'Sonnet 4.5',
+    family: 'sonnet',
     input_tokens: 50_000,
     output_tokens: 200_000,
     cache_read_tokens: 500_000,
@@ -16,6 +17,7 @@ const mockModels: ModelUsage[] = [
   {
     model: 'claude-opus-4-5-20251101',
     display_name: 'Opus 4.5',
+    family: 'opus',
     input_tokens: 10_000,
     output_tokens: 50_000,
     cache_read_tokens: 100_000,
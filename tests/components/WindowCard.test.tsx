@@ -10,6 +10,13 @@ const mockUsage: WindowUsage = {
   total_cache_creation_tokens: 50_000,
   message_count: 42,
   session_count: 3,
+  prompts: {
+    prompt_count: 6,
+    response_count: 42,
+    responses_per_prompt: 7,
+    tokens_per_prompt: 367_500,
+    cost_per_prompt: 0.39,
+  },
   window_start: '2026-02-06T10:00:00Z',
   window_end: '2026-02-06T15:00:00Z',
 };